@@ -1,3 +1,68 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// why an arithmetic operation on two values failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArithmeticError {
+    /// the operation is not defined for the operand types
+    InvalidOperands {
+        lhs: &'static str,
+        rhs: &'static str,
+    },
+    /// a string repeated a negative number of times
+    NegativeCount,
+    /// integer division or remainder by zero
+    DivisionByZero,
+    /// the integer result does not fit into an `i64`
+    Overflow,
+    /// the array shapes cannot be broadcast to a common shape
+    ShapeMismatch { lhs: Vec<usize>, rhs: Vec<usize> },
+    /// an array whose elements have different shapes, it has no shape to broadcast
+    Ragged,
+    /// the result would be longer than `MAX_LENGTH`
+    TooLarge,
+}
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOperands { lhs, rhs } => write!(f, "invalid operands `{}` and `{}`", lhs, rhs),
+            Self::NegativeCount => write!(f, "negative repetition count"),
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::Overflow => write!(f, "integer overflow"),
+            Self::ShapeMismatch { lhs, rhs } => {
+                write!(f, "shapes {:?} and {:?} cannot be broadcast", lhs, rhs)
+            }
+            Self::Ragged => write!(f, "ragged array"),
+            Self::TooLarge => write!(f, "the result is longer than {}", MAX_LENGTH),
+        }
+    }
+}
+
+impl ArithmeticError {
+    fn invalid(lhs: &DataType, rhs: &DataType) -> Self {
+        Self::InvalidOperands {
+            lhs: lhs.type_name(),
+            rhs: rhs.type_name(),
+        }
+    }
+}
+
+fn checked(result: Option<i64>) -> Result<i64, ArithmeticError> {
+    result.ok_or(ArithmeticError::Overflow)
+}
+
+fn checked_div(lhs: i64, rhs: i64) -> Result<i64, ArithmeticError> {
+    if rhs == 0 {
+        return Err(ArithmeticError::DivisionByZero);
+    }
+    // i64::MIN / -1
+    checked(lhs.checked_div(rhs))
+}
+
+/// integer arithmetic is checked and fails instead of wrapping or panicking,
+/// float arithmetic follows IEEE 754.
+/// if either operand is a `Float`, an `Int` operand is promoted to `Float`,
+/// so mixed arithmetic always results in a `Float`
 #[derive(Clone, Debug, PartialEq)]
 pub enum DataType {
     String(String),
@@ -6,207 +71,535 @@ pub enum DataType {
     Bool(bool),
 }
 impl DataType {
-    pub fn add(&self, rhs: Self) -> Option<Self> {
-        match self.clone() {
-            DataType::String(sl) => match rhs {
-                DataType::String(sr) => Some(DataType::String(sl + &sr)),
-                DataType::Float(nr) => Some(DataType::String(sl + &format!("{}", nr))),
-                DataType::Int(nr) => Some(DataType::String(sl + &format!("{}", nr))),
-                _ => None,
-            },
-            DataType::Float(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Float(nl + nr)),
-                DataType::Int(nr) => Some(DataType::Float(nl + nr as f64)),
-                _ => None,
-            },
-            DataType::Int(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Int(nl + nr as i64)),
-                DataType::Int(nr) => Some(DataType::Int(nl + nr)),
-                _ => None,
-            },
-            _ => None,
+    /// name of the type, as used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DataType::String(_) => "String",
+            DataType::Float(_) => "Float",
+            DataType::Int(_) => "Int",
+            DataType::Bool(_) => "Bool",
         }
     }
-    pub fn sub(&self, rhs: Self) -> Option<Self> {
-        match self.clone() {
-            DataType::Float(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Float(nl - nr)),
-                DataType::Int(nr) => Some(DataType::Float(nl - nr as f64)),
-                _ => None,
-            },
-            DataType::Int(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Int(nl - nr as i64)),
-                DataType::Int(nr) => Some(DataType::Int(nl - nr)),
-                _ => None,
-            },
-            _ => None,
+
+    /// adds numbers, or concatenates if either side is a string,
+    /// the other side may then be a number or a bool
+    pub fn add(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                Ok(DataType::Int(checked(nl.checked_add(nr))?))
+            }
+            (DataType::String(sl), rhs) => concat(format!("{}{}", sl, rhs)),
+            (lhs, DataType::String(sr)) => concat(format!("{}{}", lhs, sr)),
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl + nr))
+            }
         }
     }
-    pub fn mul(&self, rhs: Self) -> Option<Self> {
-        match self.clone() {
-            DataType::Float(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Float(nl * nr)),
-                DataType::Int(nr) => Some(DataType::Float(nl * nr as f64)),
-                _ => None,
-            },
-            DataType::Int(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Int(nl * nr as i64)),
-                DataType::Int(nr) => Some(DataType::Int(nl * nr)),
-                _ => None,
-            },
-            _ => None,
+    pub fn sub(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                Ok(DataType::Int(checked(nl.checked_sub(nr))?))
+            }
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl - nr))
+            }
         }
     }
-    pub fn div(&self, rhs: Self) -> Option<Self> {
-        match self.clone() {
-            DataType::Float(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Float(nl / nr)),
-                DataType::Int(nr) => Some(DataType::Float(nl / nr as f64)),
-                _ => None,
-            },
-            DataType::Int(nl) => match rhs {
-                DataType::String(_) => None,
-                DataType::Float(nr) => Some(DataType::Int(nl / nr as i64)),
-                DataType::Int(nr) => Some(DataType::Int(nl / nr)),
-                _ => None,
+    /// multiplies numbers, or repeats a string an `Int` number of times
+    pub fn mul(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                Ok(DataType::Int(checked(nl.checked_mul(nr))?))
+            }
+            (DataType::String(s), DataType::Int(n)) => Ok(DataType::String(repeat(s, n)?)),
+            (DataType::Int(n), DataType::String(s)) => Ok(DataType::String(repeat(&s, *n)?)),
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl * nr))
+            }
+        }
+    }
+    /// dividing by a float zero does not fail, it results in `inf`, `-inf` or `NaN`,
+    /// only an integer divided by an integer zero fails
+    pub fn div(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => Ok(DataType::Int(checked_div(*nl, nr)?)),
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl / nr))
+            }
+        }
+    }
+    /// the remainder has the sign of the dividend, like `div` it only fails for an integer zero
+    pub fn rem(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                if nr == 0 {
+                    return Err(ArithmeticError::DivisionByZero);
+                }
+                // i64::MIN % -1
+                Ok(DataType::Int(checked(nl.checked_rem(nr))?))
+            }
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl % nr))
+            }
+        }
+    }
+    /// an `Int` raised to a negative `Int` results in a `Float`
+    pub fn pow(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) if nr < 0 => {
+                Ok(DataType::Float((*nl as f64).powf(nr as f64)))
+            }
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                let exponent = u32::try_from(nr).map_err(|_| ArithmeticError::Overflow)?;
+                Ok(DataType::Int(checked(nl.checked_pow(exponent))?))
+            }
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, &rhs)?;
+                Ok(DataType::Float(nl.powf(nr)))
+            }
+        }
+    }
+    /// defined for two `Int`s, or two `Bool`s as logical and
+    pub fn bit_and(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => Ok(DataType::Int(nl & nr)),
+            (DataType::Bool(bl), DataType::Bool(br)) => Ok(DataType::Bool(*bl & br)),
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+    pub fn bit_or(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => Ok(DataType::Int(nl | nr)),
+            (DataType::Bool(bl), DataType::Bool(br)) => Ok(DataType::Bool(*bl | br)),
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+    pub fn bit_xor(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => Ok(DataType::Int(nl ^ nr)),
+            (DataType::Bool(bl), DataType::Bool(br)) => Ok(DataType::Bool(*bl ^ br)),
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+    /// shifting by a negative amount or by 64 bits or more overflows
+    pub fn shl(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                let amount = u32::try_from(nr).map_err(|_| ArithmeticError::Overflow)?;
+                Ok(DataType::Int(checked(nl.checked_shl(amount))?))
+            }
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+    /// arithmetic shift, the sign is kept
+    pub fn shr(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Int(nl), DataType::Int(nr)) => {
+                let amount = u32::try_from(nr).map_err(|_| ArithmeticError::Overflow)?;
+                Ok(DataType::Int(checked(nl.checked_shr(amount))?))
+            }
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+
+    /// logical and, both sides are evaluated, see `Context` for short circuiting
+    pub fn and(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Bool(bl), DataType::Bool(br)) => Ok(DataType::Bool(*bl && br)),
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+    pub fn or(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        match (self, rhs) {
+            (DataType::Bool(bl), DataType::Bool(br)) => Ok(DataType::Bool(*bl || br)),
+            (lhs, rhs) => Err(ArithmeticError::invalid(lhs, &rhs)),
+        }
+    }
+
+    /// values of the same type are compared, an `Int` is promoted when compared to a `Float`,
+    /// values of other different types are never equal
+    pub fn eq(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        let equal = match (self, &rhs) {
+            (DataType::String(sl), DataType::String(sr)) => sl == sr,
+            (DataType::Bool(bl), DataType::Bool(br)) => bl == br,
+            (DataType::Int(nl), DataType::Int(nr)) => nl == nr,
+            (lhs, rhs) => match Self::promote(lhs, rhs) {
+                Ok((nl, nr)) => nl == nr,
+                Err(_) => false,
             },
+        };
+        Ok(DataType::Bool(equal))
+    }
+    pub fn ne(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        self.eq(rhs)?.not()
+    }
+    pub fn lt(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        self.compare(rhs, |o| o == Ordering::Less)
+    }
+    pub fn le(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        self.compare(rhs, |o| o != Ordering::Greater)
+    }
+    pub fn gt(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        self.compare(rhs, |o| o == Ordering::Greater)
+    }
+    pub fn ge(&self, rhs: Self) -> Result<Self, ArithmeticError> {
+        self.compare(rhs, |o| o != Ordering::Less)
+    }
+
+    pub fn not(&self) -> Result<Self, ArithmeticError> {
+        match self {
+            DataType::Bool(b) => Ok(DataType::Bool(!b)),
+            _ => Err(ArithmeticError::invalid(self, self)),
+        }
+    }
+
+    /// numbers are ordered by value, strings lexicographically by their bytes,
+    /// comparisons with `NaN` are always false
+    fn compare(&self, rhs: Self, test: fn(Ordering) -> bool) -> Result<Self, ArithmeticError> {
+        let ordering = match (self, &rhs) {
+            (DataType::String(sl), DataType::String(sr)) => Some(sl.cmp(sr)),
+            (DataType::Int(nl), DataType::Int(nr)) => Some(nl.cmp(nr)),
+            (lhs, rhs) => {
+                let (nl, nr) = Self::promote(lhs, rhs)?;
+                nl.partial_cmp(&nr)
+            }
+        };
+        Ok(DataType::Bool(ordering.is_some_and(test)))
+    }
+
+    /// both operands as floats, if both are numbers
+    fn promote(lhs: &Self, rhs: &Self) -> Result<(f64, f64), ArithmeticError> {
+        let float = |n: &Self| match n {
+            DataType::Float(n) => Some(*n),
+            DataType::Int(n) => Some(*n as f64),
             _ => None,
+        };
+        match (float(lhs), float(rhs)) {
+            (Some(nl), Some(nr)) => Ok((nl, nr)),
+            _ => Err(ArithmeticError::invalid(lhs, rhs)),
+        }
+    }
+}
+
+/// the most bytes of a string or elements of an array a single operation may create,
+/// so scripts cannot exhaust the memory of the host
+pub const MAX_LENGTH: usize = 1 << 24;
+
+fn concat(s: String) -> Result<DataType, ArithmeticError> {
+    match s.len() <= MAX_LENGTH {
+        true => Ok(DataType::String(s)),
+        false => Err(ArithmeticError::TooLarge),
+    }
+}
+
+fn repeat(s: &str, count: i64) -> Result<String, ArithmeticError> {
+    let count = usize::try_from(count).map_err(|_| ArithmeticError::NegativeCount)?;
+    match s.len().checked_mul(count) {
+        Some(length) if length <= MAX_LENGTH => Ok(s.repeat(count)),
+        _ => Err(ArithmeticError::TooLarge),
+    }
+}
+
+/// the value as it is written in a script, except that strings are not quoted
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{}", s),
+            // `{:?}` keeps the fraction of whole numbers, `1.0` instead of `1`
+            Self::Float(n) => write!(f, "{:?}", n),
+            Self::Int(n) => write!(f, "{}", n),
+            Self::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+impl DataType {
+    /// like `Display`, but strings are quoted and escaped as string literals
+    pub fn repr(&self) -> String {
+        match self {
+            Self::String(s) => format!("\"{}\"", escape(s)),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// escapes `s` so it can be written between the quotes of a string literal
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            // braces start an interpolation, they are escaped by doubling them
+            '{' => escaped.push_str("{{"),
+            '}' => escaped.push_str("}}"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
         }
     }
+    escaped
 }
-// impl std::fmt::Debug for DataType {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         match self {
-//             Self::String(s) => write!(f, "'{}'", s),
-//             Self::Float(n) => write!(f, "{}", n),
-//             Self::Int(i) => write!(f, "{}", i),
-//             Self::Bool(b) => write!(f, "{}", b),
-//         }
-//     }
-// }
 
 #[derive(Clone, Debug)]
 pub enum Value {
     Data(DataType),
     Array(Vec<Value>),
+    /// the value of statements, blocks without a tail and functions that return nothing
+    Unit,
 }
+/// arithmetic and ordering on values broadcast like NumPy:
+/// shapes are compared from their last dimension, two dimensions are compatible if they are
+/// equal or one of them is 1, and a missing dimension counts as 1.
+/// so a scalar combines with every element of an array, on either side,
+/// `[[1, 2], [3, 4]] + [10, 20]` adds `[10, 20]` to every row
+/// and `[[1], [2]] * [1, 2, 3]` results in a 2 by 3 array
 impl Value {
-    pub fn add(&self, rhs: Value) -> Option<Self> {
-        match self.clone() {
-            Value::Data(dl) => match rhs {
-                Value::Data(dr) => dl.add(dr).map(|d| Value::Data(d)),
-                Value::Array(_) => None,
-            },
-            Value::Array(mut ar) => match rhs {
-                Value::Data(dl) => {
-                    for a in ar.iter_mut() {
-                        *a = a.add(Value::Data(dl.clone()))?;
-                    }
-                    Some(Value::Array(ar))
-                }
-                Value::Array(al) => {
-                    for (i, a) in ar.iter_mut().enumerate() {
-                        *a = a.add(
-                            al.get(i)
-                                .cloned()
-                                .unwrap_or(Value::Data(DataType::Float(0.0))),
-                        )?;
-                    }
-                    Some(Value::Array(ar))
-                }
-            },
+    pub fn add(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::add)
+    }
+    pub fn sub(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::sub)
+    }
+    pub fn mul(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::mul)
+    }
+    pub fn div(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::div)
+    }
+    pub fn rem(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::rem)
+    }
+    pub fn pow(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::pow)
+    }
+    pub fn bit_and(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::bit_and)
+    }
+    pub fn bit_or(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::bit_or)
+    }
+    pub fn bit_xor(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::bit_xor)
+    }
+    pub fn shl(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::shl)
+    }
+    pub fn shr(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::shr)
+    }
+    pub fn and(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::and)
+    }
+    pub fn or(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::or)
+    }
+    /// unlike the other operators, compares whole values to a single `Bool`:
+    /// arrays are equal if they have the same length and equal elements in order,
+    /// elements compare like `DataType::eq`, values of different types are not equal
+    pub fn eq(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        Ok(Value::Data(DataType::Bool(self.equals(&rhs))))
+    }
+    pub fn ne(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        Ok(Value::Data(DataType::Bool(!self.equals(&rhs))))
+    }
+    fn equals(&self, rhs: &Value) -> bool {
+        match (self, rhs) {
+            (Value::Data(l), Value::Data(r)) => matches!(l.eq(r.clone()), Ok(DataType::Bool(true))),
+            (Value::Array(l), Value::Array(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l.equals(r))
+            }
+            (Value::Unit, Value::Unit) => true,
+            _ => false,
         }
     }
-    pub fn sub(&self, rhs: Value) -> Option<Self> {
-        match self.clone() {
-            Value::Data(dl) => match rhs {
-                Value::Data(dr) => dl.sub(dr).map(|d| Value::Data(d)),
-                Value::Array(_) => None,
-            },
-            Value::Array(mut ar) => match rhs {
-                Value::Data(dl) => {
-                    for a in ar.iter_mut() {
-                        *a = a.sub(Value::Data(dl.clone()))?;
-                    }
-                    Some(Value::Array(ar))
-                }
-                Value::Array(al) => {
-                    for (i, a) in ar.iter_mut().enumerate() {
-                        *a = a.sub(
-                            al.get(i)
-                                .cloned()
-                                .unwrap_or(Value::Data(DataType::Float(0.0))),
-                        )?;
-                    }
-                    Some(Value::Array(ar))
-                }
-            },
+    pub fn lt(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::lt)
+    }
+    pub fn le(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::le)
+    }
+    pub fn gt(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::gt)
+    }
+    pub fn ge(&self, rhs: Value) -> Result<Self, ArithmeticError> {
+        self.broadcast(&rhs, DataType::ge)
+    }
+
+    /// negates every element
+    pub fn not(&self) -> Result<Self, ArithmeticError> {
+        match self {
+            Value::Data(d) => Ok(Value::Data(d.not()?)),
+            Value::Array(a) => Ok(Value::Array(a.iter().map(Value::not).collect::<Result<_, _>>()?)),
+            Value::Unit => Err(ArithmeticError::InvalidOperands {
+                lhs: "Unit",
+                rhs: "Unit",
+            }),
         }
     }
-    pub fn mul(&self, rhs: Value) -> Option<Self> {
-        match self.clone() {
-            Value::Data(dl) => match rhs {
-                Value::Data(dr) => dl.mul(dr).map(|d| Value::Data(d)),
-                Value::Array(_) => None,
-            },
-            Value::Array(mut ar) => match rhs {
-                Value::Data(dl) => {
-                    for a in ar.iter_mut() {
-                        *a = a.mul(Value::Data(dl.clone()))?;
-                    }
-                    Some(Value::Array(ar))
-                }
-                Value::Array(al) => {
-                    for (i, a) in ar.iter_mut().enumerate() {
-                        *a = a.mul(
-                            al.get(i)
-                                .cloned()
-                                .unwrap_or(Value::Data(DataType::Float(1.0))),
-                        )?;
-                    }
-                    Some(Value::Array(ar))
-                }
-            },
+
+    /// name of the type, as used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Data(d) => d.type_name(),
+            Value::Array(_) => "Array",
+            Value::Unit => "Unit",
         }
     }
-    pub fn div(&self, rhs: Value) -> Option<Self> {
-        match self.clone() {
-            Value::Data(dl) => match rhs {
-                Value::Data(dr) => dl.div(dr).map(|d| Value::Data(d)),
-                Value::Array(_) => None,
-            },
-            Value::Array(mut ar) => match rhs {
-                Value::Data(dl) => {
-                    for a in ar.iter_mut() {
-                        *a = a.div(Value::Data(dl.clone()))?;
-                    }
-                    Some(Value::Array(ar))
-                }
-                Value::Array(al) => {
-                    for (i, a) in ar.iter_mut().enumerate() {
-                        *a = a.div(
-                            al.get(i)
-                                .cloned()
-                                .unwrap_or(Value::Data(DataType::Float(1.0))),
-                        )?;
-                    }
-                    Some(Value::Array(ar))
+
+    /// like `Display`, but strings are quoted and escaped as string literals
+    pub fn repr(&self) -> String {
+        match self {
+            Self::Data(d) => d.repr(),
+            Self::Array(array) => {
+                let elements: Vec<String> = array.iter().map(|v| v.repr()).collect();
+                format!("[{}]", elements.join(", "))
+            }
+            Self::Unit => self.to_string(),
+        }
+    }
+
+    /// a total order over all values, used for sorting.
+    /// values of different types are ordered `Bool`, numbers, `String`, `Array`, `Unit`.
+    /// `Int` and `Float` compare by value with `NaN` after every other number,
+    /// arrays compare their elements in order
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        let rank = |v: &Value| match v {
+            Value::Data(DataType::Bool(_)) => 0,
+            Value::Data(DataType::Int(_) | DataType::Float(_)) => 1,
+            Value::Data(DataType::String(_)) => 2,
+            Value::Array(_) => 3,
+            Value::Unit => 4,
+        };
+        match (self, other) {
+            (Value::Data(DataType::Bool(l)), Value::Data(DataType::Bool(r))) => l.cmp(r),
+            (Value::Data(DataType::Int(l)), Value::Data(DataType::Int(r))) => l.cmp(r),
+            (Value::Data(DataType::String(l)), Value::Data(DataType::String(r))) => l.cmp(r),
+            (Value::Data(l), Value::Data(r)) if rank(self) == rank(other) => {
+                match DataType::promote(l, r) {
+                    Ok((l, r)) => l
+                        .is_nan()
+                        .cmp(&r.is_nan())
+                        .then(l.partial_cmp(&r).unwrap_or(Ordering::Equal)),
+                    Err(_) => Ordering::Equal,
                 }
-            },
+            }
+            (Value::Array(l), Value::Array(r)) => l
+                .iter()
+                .zip(r)
+                .map(|(l, r)| l.total_cmp(r))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(l.len().cmp(&r.len())),
+            _ => rank(self).cmp(&rank(other)),
         }
     }
+
+    /// length of every dimension, empty for scalars, `None` if the array is ragged
+    pub fn shape(&self) -> Option<Vec<usize>> {
+        let Value::Array(array) = self else {
+            return Some(Vec::new());
+        };
+        let mut elements = array.iter().map(|v| v.shape());
+        let inner = match elements.next() {
+            Some(first) => first?,
+            None => Vec::new(),
+        };
+        for shape in elements {
+            if shape? != inner {
+                return None;
+            }
+        }
+        Some(std::iter::once(array.len()).chain(inner).collect())
+    }
+
+    /// applies `op` to every pair of elements of the broadcast operands
+    pub fn broadcast(
+        &self,
+        rhs: &Value,
+        op: fn(&DataType, DataType) -> Result<DataType, ArithmeticError>,
+    ) -> Result<Self, ArithmeticError> {
+        let lhs_shape = self.shape().ok_or(ArithmeticError::Ragged)?;
+        let rhs_shape = rhs.shape().ok_or(ArithmeticError::Ragged)?;
+        let compatible = lhs_shape
+            .iter()
+            .rev()
+            .zip(rhs_shape.iter().rev())
+            .all(|(l, r)| l == r || *l == 1 || *r == 1);
+        if !compatible {
+            return Err(ArithmeticError::ShapeMismatch {
+                lhs: lhs_shape,
+                rhs: rhs_shape,
+            });
+        }
+        broadcast_with(self, rhs, lhs_shape.len(), rhs_shape.len(), op)
+    }
+}
+
+/// array elements are shown by `repr`, so `[1, 2.5, "a"]` displays as it is written
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Data(d) => write!(f, "{}", d),
+            Self::Array(_) => write!(f, "{}", self.repr()),
+            Self::Unit => write!(f, "()"),
+        }
+    }
+}
+
+/// `lhs_rank` and `rhs_rank` are the number of dimensions left, the shapes are compatible
+fn broadcast_with(
+    lhs: &Value,
+    rhs: &Value,
+    lhs_rank: usize,
+    rhs_rank: usize,
+    op: fn(&DataType, DataType) -> Result<DataType, ArithmeticError>,
+) -> Result<Value, ArithmeticError> {
+    let values = match (lhs, rhs) {
+        (Value::Data(l), Value::Data(r)) => return op(l, r.clone()).map(Value::Data),
+        // the operand with fewer dimensions is repeated along the outer dimension of the other
+        (Value::Array(l), _) if lhs_rank > rhs_rank => l
+            .iter()
+            .map(|l| broadcast_with(l, rhs, lhs_rank - 1, rhs_rank, op))
+            .collect::<Result<_, _>>()?,
+        (_, Value::Array(r)) if rhs_rank > lhs_rank => r
+            .iter()
+            .map(|r| broadcast_with(lhs, r, lhs_rank, rhs_rank - 1, op))
+            .collect::<Result<_, _>>()?,
+        // a dimension of length 1 is stretched to the length of the other
+        (Value::Array(l), Value::Array(r)) => (0..if l.len() == 1 { r.len() } else { l.len() })
+            .map(|i| {
+                let l = &l[if l.len() == 1 { 0 } else { i }];
+                let r = &r[if r.len() == 1 { 0 } else { i }];
+                broadcast_with(l, r, lhs_rank - 1, rhs_rank - 1, op)
+            })
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(ArithmeticError::InvalidOperands {
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            })
+        }
+    };
+    Ok(Value::Array(values))
 }
 
 pub trait IntoValue {
+    #[allow(clippy::wrong_self_convention)]
     fn into_value(&self) -> Value;
 }
+impl IntoValue for Value {
+    fn into_value(&self) -> Value {
+        self.clone()
+    }
+}
 impl IntoValue for &str {
     fn into_value(&self) -> Value {
         Value::Data(DataType::String(self.to_string()))
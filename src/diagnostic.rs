@@ -0,0 +1,212 @@
+use colored::*;
+use std::fmt;
+
+use crate::source::SourceMap;
+use crate::span::Span;
+
+/// stable error codes, displayed as `E0003`,
+/// existing numbers must never be reused or changed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidToken = 1,
+    Syntax = 2,
+    UndeclaredFunction = 3,
+    ArgumentCount = 4,
+    UndeclaredVariable = 5,
+    InvalidOperands = 6,
+    /// reserved, no longer emitted
+    MissingValue = 7,
+    RecursionLimit = 8,
+    DivisionByZero = 9,
+    Overflow = 10,
+    ShapeMismatch = 11,
+    TypeMismatch = 12,
+    InvalidValue = 13,
+    InvalidArgument = 14,
+    PermissionDenied = 15,
+    Io = 16,
+    ConstantAssignment = 17,
+    NestedFunction = 18,
+    TooLarge = 19,
+}
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", *self as u16)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+impl Label {
+    pub fn new(span: Span, message: String) -> Self {
+        Self { span, message }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+impl Diagnostic {
+    pub fn error(message: String, span: Span) -> Self {
+        Self {
+            code: None,
+            message,
+            primary: Label::new(span, String::new()),
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    /// `title` names the stage that failed, e.g. "parse error"
+    pub fn format_with(&self, sources: &SourceMap, title: &str, color: bool) -> String {
+        colored::control::set_override(color);
+
+        let title = match self.code {
+            Some(code) => format!("{}[{}]", title, code),
+            None => title.to_string(),
+        };
+        let mut output = format!("{}:", title.red().bold());
+
+        // all snippets share the width of the largest line number
+        let width = std::iter::once(&self.primary)
+            .chain(self.secondary.iter())
+            .filter_map(|label| Some(sources.file_of(label.span)?.lines(label.span).last()?.0))
+            .max()
+            .unwrap_or(1)
+            .to_string()
+            .len();
+
+        output.push_str(&render_label(sources, &self.primary, '^', "-->", width));
+        for label in self.secondary.iter() {
+            output.push_str(&render_label(sources, label, '-', ":::", width));
+        }
+
+        output.push_str(&format!("\n{}", self.message.red()));
+        for note in self.notes.iter() {
+            output.push_str(&format!("\n{} {} {}", " ".repeat(width), "= note:".bold(), note));
+        }
+        for help in self.help.iter() {
+            output.push_str(&format!("\n{} {} {}", " ".repeat(width), "= help:".bold(), help));
+        }
+
+        output
+    }
+}
+
+/// prints the location and every line touched by the label, underlining exactly the spanned range
+fn render_label(
+    sources: &SourceMap,
+    label: &Label,
+    marker: char,
+    arrow: &str,
+    width: usize,
+) -> String {
+    let Some(file) = sources.file_of(label.span) else {
+        return String::new();
+    };
+    let mut output = String::new();
+    let span = label.span;
+    let start = file.clamp(span.start);
+    let end = file.clamp(span.end.max(span.start));
+    let gutter = format!("{} {}", " ".repeat(width), "▍".blue().bold());
+
+    if let Some(location) = sources.location(span) {
+        output.push_str(&format!(
+            "\n{}{} {}",
+            " ".repeat(width),
+            arrow.blue().bold(),
+            location
+        ));
+    }
+    output.push_str(&format!("\n{}", gutter));
+    let lines = file.lines(span);
+    let count = lines.len();
+    for (i, (number, line_start, line)) in lines.into_iter().enumerate() {
+        let from = (start.max(line_start) - line_start).min(line.len());
+        let to = (end.max(line_start) - line_start).min(line.len());
+        // keep tabs, so the underline lines up with the printed line
+        let offset: String = line[..from]
+            .chars()
+            .map(|c| if c == '\t' { c } else { ' ' })
+            .collect();
+        // empty ranges still get a single marker
+        let underscore_width = line[from..to].chars().count().max(1);
+        let underline = marker.to_string().repeat(underscore_width);
+        let underline = match marker {
+            '^' => underline.cyan(),
+            _ => underline.blue(),
+        };
+        // the message is attached to the last line of the label
+        let message = match i + 1 == count && !label.message.is_empty() {
+            true => format!(" {}", label.message),
+            false => String::new(),
+        };
+
+        output.push_str(&format!(
+            "\n{} {} {}",
+            format!("{:>width$}", number).blue().bold(),
+            "▍".blue().bold(),
+            line,
+        ));
+        output.push_str(&format!("\n{} {}{}{}", gutter, offset, underline, message));
+    }
+
+    output
+}
+
+/// the candidate closest to `name`, if it is close enough to be a likely typo
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = name.chars().count().div_ceil(3);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -1,141 +1,281 @@
 use colored::*;
+use std::ops::{Deref, DerefMut};
 
-pub trait ExecutionErrorExt<T> {
-    fn idc(self) -> Result<T, ExecutionError>;
+use crate::diagnostic::{Diagnostic, ErrorCode, Label};
+use crate::lexer::TokenKind;
+use crate::parser::BinaryOperator;
+use crate::source::SourceMap;
+use crate::span::Span;
+
+/// a function call that was active when an execution error occurred
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub function: String,
+    /// `None` for the entry function called by the host
+    pub call_site: Option<Span>,
+}
+
+/// boxed, so results stay small on the hot path of the recursive evaluation
+#[derive(Clone, Debug)]
+pub struct ExecutionError(Box<ExecutionErrorData>);
+
+#[derive(Clone, Debug)]
+pub struct ExecutionErrorData {
+    pub span: Span,
+    pub cause: String,
+    pub code: Option<ErrorCode>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    /// innermost call first, the error occurred inside the first function
+    pub backtrace: Vec<Frame>,
 }
+impl ExecutionError {
+    pub fn new(span: Span, cause: String) -> Self {
+        Self(Box::new(ExecutionErrorData {
+            span,
+            cause,
+            code: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            backtrace: Vec::new(),
+        }))
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.labels.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    /// the diagnostic followed by the location of the error and every caller
+    pub fn format_with(&self, sources: &SourceMap, title: &str, color: bool) -> String {
+        let mut output = Diagnostic::from(self.clone()).format_with(sources, title, color);
+        let Some(innermost) = self.backtrace.first() else {
+            return output;
+        };
+        let location = |span: Span| match sources.location(span) {
+            Some(location) => location.to_string(),
+            None => "<unknown>".to_string(),
+        };
 
-// Implement the trait for Option
-impl<T> ExecutionErrorExt<T> for Option<T> {
-    fn idc(self) -> Result<T, ExecutionError> {
-        let error = ExecutionError::new(0, String::new());
-        match self {
-            Some(value) => Ok(value),
-            None => Err(error),
+        output.push_str(&format!("\n{}", "backtrace:".bold()));
+        output.push_str(&format!(
+            "\n    in '{}' at {}",
+            innermost.function,
+            location(self.span)
+        ));
+        // recursion repeats the same line, only the first is shown
+        let mut previous = String::new();
+        let mut repeated = 0;
+        for (i, frame) in self.backtrace.iter().enumerate() {
+            let Some(call_site) = frame.call_site else {
+                continue;
+            };
+            let caller = match self.backtrace.get(i + 1) {
+                Some(caller) => format!("'{}'", caller.function),
+                None => "top level".to_string(),
+            };
+            let line = format!("\n    called from {} at {}", caller, location(call_site));
+            if line == previous {
+                repeated += 1;
+                continue;
+            }
+            push_repeated(&mut output, repeated);
+            repeated = 0;
+            output.push_str(&line);
+            previous = line;
         }
+        push_repeated(&mut output, repeated);
+
+        output
     }
 }
+impl Deref for ExecutionError {
+    type Target = ExecutionErrorData;
 
-pub trait ParseErrorExt<T> {
-    fn idc(self) -> Result<T, ParseError>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
-
-// Implement the trait for Option
-impl<T> ParseErrorExt<T> for Option<T> {
-    fn idc(self) -> Result<T, ParseError> {
-        let error = ParseError::new(0, 0, String::new());
-        match self {
-            Some(value) => Ok(value),
-            None => Err(error),
+impl DerefMut for ExecutionError {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl From<ExecutionError> for Diagnostic {
+    fn from(error: ExecutionError) -> Self {
+        let error = *error.0;
+        Self {
+            code: error.code,
+            secondary: error.labels,
+            notes: error.notes,
+            help: error.help,
+            ..Diagnostic::error(error.cause, error.span)
         }
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct ExecutionError {
-    pub at: usize,
-    pub cause: String,
-}
-impl ExecutionError {
-    pub fn new(at: usize, cause: String) -> Self {
-        Self { at, cause }
-    }
-
-    pub fn format_with(&self, input: &str, title: &str, color: bool) -> String {
-        colored::control::set_override(color);
-
-        let underscore_width = 5;
-        let (line, start_offset) = get_line_at_index(input, self.at);
-        let line_number = get_line_number(input, self.at);
-
-        let header = format!(
-            "{}:\n  {}",
-            title.red().bold(),
-            "▍".blue().bold()
-        );
-        let body = format!(
-            "{} {} {}",
-            format!("{}", line_number).blue().bold(),
-            "▍".blue().bold(),
-            line
-        );
-        let footer = format!(
-            "  {} {} {}",
-            "▍".blue().bold(),
-            start_offset,
-            "^".repeat(underscore_width).cyan(),
-        );
-        let footer = format!("{}\n{}", footer, self.cause.red());
-
-        format!("{}\n{}\n{}", header, body, footer)
+fn push_repeated(output: &mut String, repeated: usize) {
+    if repeated > 0 {
+        output.push_str(&format!("\n    ... repeated {} more time(s)", repeated));
     }
 }
 
+/// boxed like `ExecutionError`, every alternative the parser tries returns one
 #[derive(Clone, Debug)]
-pub struct ParseError {
-    pub at: usize,
-    pub depth: u32,
+pub struct ParseError(Box<ParseErrorData>);
+
+#[derive(Clone, Debug)]
+pub struct ParseErrorData {
+    pub span: Span,
     pub cause: String,
+    /// tokens that would have been accepted at `span`, empty for errors with a custom cause
+    pub expected: Vec<TokenKind>,
+    /// `None` at the end of the input
+    pub found: Option<TokenKind>,
+    pub code: ErrorCode,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
 }
 impl ParseError {
-    pub fn new(at: usize, depth: u32, cause: String) -> Self {
-        Self { at, depth, cause }
-    }
-
-    pub fn format_with(&self, input: &str, title: &str, color: bool) -> String {
-        colored::control::set_override(color);
-
-        let underscore_width = 5;
-        let (line, start_offset) = get_line_at_index(input, self.at);
-        let line_number = get_line_number(input, self.at);
-
-        let header = format!(
-            "{}:\n  {}",
-            title.red().bold(),
-            "▍".blue().bold()
-        );
-        let body = format!(
-            "{} {} {}",
-            format!("{}", line_number).blue().bold(),
-            "▍".blue().bold(),
-            line
-        );
-        let footer = format!(
-            "  {} {} {}",
-            "▍".blue().bold(),
-            start_offset,
-            "^".repeat(underscore_width).cyan(),
-        );
-        let footer = format!("{}\n{}", footer, self.cause.red());
-
-        format!("{}\n{}\n{}", header, body, footer)
+    pub fn new(span: Span, cause: String) -> Self {
+        Self(Box::new(ParseErrorData {
+            span,
+            cause,
+            expected: Vec::new(),
+            found: None,
+            code: ErrorCode::Syntax,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }))
+    }
+
+    pub fn expected(span: Span, expected: Vec<TokenKind>, found: Option<TokenKind>) -> Self {
+        let mut error = Self::new(span, String::new());
+        error.found = found;
+        for kind in expected {
+            error.expect(kind);
+        }
+        error.cause = error.expectation_cause();
+        error
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
     }
-}
 
-fn get_line_at_index(text: &str, index: usize) -> (String, String) {
-    let mut line_start = 0;
-    let mut line_end = 0;
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
 
-    // Find the start and end indices of the line containing the given index
-    for (i, c) in text.chars().enumerate() {
-        if c == '\n' {
-            if i >= index {
-                break;
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    /// combines errors of alternatives, the error that got further wins, errors at the same
+    /// position merge their expected tokens, custom causes are kept over expectations
+    pub fn merge(self, other: ParseError) -> ParseError {
+        if other.span.start > self.span.start {
+            return other;
+        }
+        if other.span.start < self.span.start {
+            return self;
+        }
+        match (self.expected.is_empty(), other.expected.is_empty()) {
+            (false, false) => {
+                let mut merged = self;
+                for kind in other.0.expected {
+                    merged.expect(kind);
+                }
+                merged.cause = merged.expectation_cause();
+                merged
             }
-            line_start = i + 1;
+            (false, true) => other,
+            _ => self,
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) {
+        if !self.expected.contains(&kind) {
+            self.expected.push(kind);
         }
-        line_end = i;
     }
 
-    // Extract the line from the text
-    let line = &text[line_start..=line_end];
-    let start_offset = index - line_start;
-    let start_offset_string: &String = &line[0..start_offset]
-        .chars()
-        .map(|c| if c == '\t' { c } else { ' ' })
-        .collect();
-    (line.to_string(), start_offset_string.clone())
+    /// e.g. "expected one of `;`, `+`, `)` but found `}`"
+    fn expectation_cause(&self) -> String {
+        let mut expected: Vec<String> = Vec::new();
+        // everything that can start an expression and every operator are summarized
+        let operators = BinaryOperator::tokens();
+        let expression = TokenKind::EXPRESSION_START
+            .iter()
+            .all(|kind| self.expected.contains(kind));
+        let operator = operators.iter().all(|kind| self.expected.contains(kind));
+        if expression {
+            expected.push("expression".to_string());
+        }
+        if operator {
+            expected.push("operator".to_string());
+        }
+        for kind in self.expected.iter() {
+            let summarized = (expression && TokenKind::EXPRESSION_START.contains(kind))
+                || (operator && operators.contains(kind));
+            if !summarized {
+                expected.push(kind.to_string());
+            }
+        }
+        let found = match &self.found {
+            Some(kind) => kind.to_string(),
+            None => "end of input".to_string(),
+        };
+        match expected.as_slice() {
+            [] => format!("unexpected {}", found),
+            [one] => format!("expected {} but found {}", one, found),
+            many => format!("expected one of {} but found {}", many.join(", "), found),
+        }
+    }
+
+    pub fn format_with(&self, sources: &SourceMap, title: &str, color: bool) -> String {
+        Diagnostic::from(self.clone()).format_with(sources, title, color)
+    }
 }
+impl Deref for ParseError {
+    type Target = ParseErrorData;
 
-fn get_line_number(text: &str, index: usize) -> usize {
-    text[0..index].chars().filter(|x| x == &'\n').count() + 1
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for ParseError {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl From<ParseError> for Diagnostic {
+    fn from(error: ParseError) -> Self {
+        let error = *error.0;
+        Self {
+            notes: error.notes,
+            help: error.help,
+            ..Diagnostic::error(error.cause, error.span).with_code(error.code)
+        }
+    }
 }
@@ -0,0 +1,64 @@
+//! checks that need the whole script but do not run any of it
+
+use std::collections::HashSet;
+
+use crate::diagnostic::{suggest, ErrorCode};
+use crate::error::ExecutionError;
+use crate::parser::{Ast, AstNode, AstNodeData};
+
+use super::context::nested_function;
+use super::functions;
+
+/// the errors `Executor::build` or a later call would report for any input:
+/// calls to functions that are neither declared nor built in, and nested function declarations
+pub fn check(ast: &Ast) -> Vec<ExecutionError> {
+    let declared: HashSet<&str> = ast
+        .nodes
+        .iter()
+        .filter_map(|node| match &node.data {
+            AstNodeData::FnDeclaration { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut errors = Vec::new();
+    for node in ast.nodes.iter() {
+        match node.data {
+            AstNodeData::FnDeclaration { .. } => {
+                for child in node.children() {
+                    check_node(child, &declared, &mut errors);
+                }
+            }
+            _ => check_node(node, &declared, &mut errors),
+        }
+    }
+    errors
+}
+
+fn check_node(node: &AstNode, declared: &HashSet<&str>, errors: &mut Vec<ExecutionError>) {
+    match &node.data {
+        AstNodeData::FnDeclaration { name, .. } => errors.push(nested_function(name, node.span)),
+        AstNodeData::FnCall { .. } => check_call(node, declared, errors),
+        _ => {}
+    }
+    for child in node.children() {
+        check_node(child, declared, errors);
+    }
+}
+
+fn check_call(node: &AstNode, declared: &HashSet<&str>, errors: &mut Vec<ExecutionError>) {
+    if let AstNodeData::FnCall { name, .. } = &node.data {
+        let inbuilt = functions::inbuilt_names();
+        if !declared.contains(name.as_str()) && !inbuilt.contains(&name.as_str()) {
+            let mut err = ExecutionError::new(
+                node.span,
+                format!("function '{}' is not declared", name),
+            )
+            .with_code(ErrorCode::UndeclaredFunction);
+            let names = inbuilt.into_iter().chain(declared.iter().copied());
+            if let Some(similar) = suggest(name, names) {
+                err = err.with_help(format!("a function with a similar name exists: '{}'", similar));
+            }
+            errors.push(err);
+        }
+    }
+}
@@ -1,71 +1,272 @@
-use crate::error::ExecutionError;
-use super::{functions, Value};
-use crate::parser::{Ast, AstNode, AstNodeData, BinaryOperator, Data, FnArgument};
+use crate::data::{ArithmeticError, DataType, MAX_LENGTH};
+use crate::diagnostic::{suggest, ErrorCode};
+use crate::error::{ExecutionError, Frame};
+use crate::span::Span;
+use super::functions::{self, BuiltinError};
+use super::host::Host;
+use super::Value;
+use crate::parser::{Ast, AstNode, AstNodeData, BinaryOperator, CallArgument, Data, FnArgument};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
+
+/// the deepest script functions may call each other, so the evaluation cannot overflow
+/// the stack of the host thread
+pub const MAX_CALL_DEPTH: usize = 256;
 
 #[derive(Clone, Debug)]
 pub struct Function {
     pub arguments: Vec<FnArgument>,
     pub returns: Option<String>,
     pub body: Box<AstNode>,
+    /// span of the declaration
+    pub span: Span,
+}
+
+/// a global, or a variable of a function or block
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub value: Value,
+    /// declared with `const`, it cannot be assigned or declared again
+    pub constant: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct Context {
-    pub variables: HashMap<String, Value>,
+    /// the variables of the running function, one scope per open block, innermost last.
+    /// a variable is dropped with its block, assignments change it where it was declared
+    scopes: Vec<HashMap<String, Variable>>,
+    /// shared with every call and kept between calls, so functions can assign them.
+    /// a function's variable with the same name hides a global
+    globals: Rc<RefCell<HashMap<String, Variable>>>,
     pub functions: HashMap<String, Function>,
+    /// active function calls, outermost first
+    pub call_stack: Vec<Frame>,
+    /// set by `return`, stops the enclosing blocks up to the function body
+    returned: bool,
+    /// what built-in functions may access, shared with every call
+    host: Rc<Host>,
 }
 impl Context {
-    pub fn new() -> Self {
+    pub fn new(host: Rc<Host>) -> Self {
         Self {
-            variables: HashMap::default(),
+            scopes: Vec::new(),
+            globals: Rc::new(RefCell::new(
+                functions::constants()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), Variable { value, constant: true }))
+                    .collect(),
+            )),
             functions: HashMap::default(),
+            call_stack: Vec::new(),
+            returned: false,
+            host,
         }
     }
 
-    pub fn build(ast: &Ast) -> Result<Self, ExecutionError> {
-        let mut ctx = Context::new();
-        ctx.collect_functions(&ast)?;
-        ctx.evaluate(&ast)?;
+    pub fn build(ast: &Ast, host: Rc<Host>) -> Result<Self, ExecutionError> {
+        let mut ctx = Context::new(host);
+        ctx.collect_functions(ast)?;
+        ctx.evaluate(ast)?;
 
         Ok(ctx)
     }
 
-    pub fn call_function(&self, name: &str, arguments: Vec<Value>) -> Result<Option<Value>, ExecutionError> {
-        match functions::call_inbuilt(name, arguments.clone()) {
-            Ok(value) => return Ok(value),
-            Err(_) => (),
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.globals.borrow().get(name).map(|global| global.value.clone())
+    }
+
+    /// every global sorted by name
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let mut globals: Vec<_> = self
+            .globals
+            .borrow()
+            .iter()
+            .map(|(name, global)| (name.clone(), global.value.clone()))
+            .collect();
+        globals.sort_by(|a, b| a.0.cmp(&b.0));
+        globals
+    }
+
+    /// declares the global `name`, or assigns it if it exists and `constant` is false.
+    /// `call_site` is `None` when the host sets it
+    pub fn set_global(
+        &self,
+        name: &str,
+        value: Value,
+        constant: bool,
+        call_site: Option<Span>,
+    ) -> Result<(), ExecutionError> {
+        let mut globals = self.globals.borrow_mut();
+        let span = call_site.unwrap_or_default();
+        match globals.get_mut(name) {
+            Some(global) if global.constant => Err(constant_assignment(name, span)),
+            Some(global) if !constant => {
+                global.value = value;
+                Ok(())
+            }
+            _ => {
+                globals.insert(name.to_string(), Variable { value, constant });
+                Ok(())
+            }
+        }
+    }
+
+    /// `call_site` is the span of the call expression, `None` when called by the host.
+    /// `named` are the `name: value` arguments, they follow the positional `arguments`
+    pub fn call_function(
+        &self,
+        name: &str,
+        arguments: Vec<Value>,
+        named: Vec<(String, Value)>,
+        call_site: Option<Span>,
+    ) -> Result<Value, ExecutionError> {
+        let span = call_site.unwrap_or_default();
+        // script functions hide built-ins with the same name
+        let function = self.functions.get(name);
+        if function.is_none() {
+            if !named.is_empty() && functions::inbuilt_names().contains(&name) {
+                let err = ExecutionError::new(
+                    span,
+                    format!("built-in function '{}' does not take named arguments", name),
+                )
+                .with_code(ErrorCode::InvalidArgument);
+                return Err(self.with_backtrace(err));
+            }
+            if let Some(result) = functions::call_inbuilt(name, arguments.clone(), &self.host) {
+                return result.map_err(|err| self.with_backtrace(builtin_error(name, span, err)));
+            }
         }
-        // TODO: clone context and insert arguments into it
-        let Some(function) = self.functions.get(name) else {
-            return Err(ExecutionError::new(
-                0,
+        let Some(function) = function else {
+            let mut err = ExecutionError::new(
+                span,
                 format!("function '{}' is not declared", name),
-            ));
+            )
+            .with_code(ErrorCode::UndeclaredFunction);
+            let names = functions::inbuilt_names()
+                .into_iter()
+                .chain(self.functions.keys().map(|k| k.as_str()));
+            if let Some(similar) = suggest(name, names) {
+                err = err.with_help(format!("a function with a similar name exists: '{}'", similar));
+            }
+            return Err(self.with_backtrace(err));
         };
-        let body = function.body.clone();
-        let arg_definitions = function.arguments.clone();
-        if arg_definitions.len() != arguments.len() {
-            return Err(ExecutionError::new(
-                0,
+        let mut ctx = self.enter(name, function, arguments, named, call_site)?;
+        ctx.handle_node(&function.body).map_err(|err| ctx.with_backtrace(err))
+    }
+
+    /// the context of a call to the script function `function`, its parameters are bound to
+    /// the arguments. kept out of `call_function`, whose frame stays on the stack for the call
+    fn enter(
+        &self,
+        name: &str,
+        function: &Function,
+        arguments: Vec<Value>,
+        named: Vec<(String, Value)>,
+        call_site: Option<Span>,
+    ) -> Result<Context, ExecutionError> {
+        let span = call_site.unwrap_or_default();
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            let err = ExecutionError::new(
+                span,
+                format!("cannot call '{}', more than {} calls are nested", name, MAX_CALL_DEPTH),
+            )
+            .with_code(ErrorCode::RecursionLimit)
+            .with_help("check that the recursion ends".to_string());
+            return Err(self.with_backtrace(err));
+        }
+        let parameters = &function.arguments;
+        let invalid = |cause: String, code: ErrorCode| {
+            let signature: Vec<String> = parameters
+                .iter()
+                .map(|a| match a.default {
+                    Some(_) => format!("{}?: {}", a.name, a.data_type),
+                    None => format!("{}: {}", a.name, a.data_type),
+                })
+                .collect();
+            let err = ExecutionError::new(span, cause)
+                .with_code(code)
+                .with_label(function.span, "function declared here".to_string())
+                .with_note(format!("'{}' takes ({})", name, signature.join(", ")));
+            self.with_backtrace(err)
+        };
+
+        if arguments.len() > parameters.len() {
+            let required = parameters.iter().filter(|a| a.default.is_none()).count();
+            let expected = match required == parameters.len() {
+                true => required.to_string(),
+                false => format!("{} to {}", required, parameters.len()),
+            };
+            return Err(invalid(
                 format!(
                     "invalid function arguments, expected {} value(s), found: {}",
-                    arg_definitions.len(),
+                    expected,
                     arguments.len()
                 ),
+                ErrorCode::ArgumentCount,
             ));
         }
+        let mut values: Vec<Option<Value>> = arguments.into_iter().map(Some).collect();
+        values.resize(parameters.len(), None);
+        for (argument, value) in named {
+            let Some(i) = parameters.iter().position(|a| a.name == argument) else {
+                let mut err = invalid(
+                    format!("function '{}' has no parameter named '{}'", name, argument),
+                    ErrorCode::InvalidArgument,
+                );
+                let names = parameters.iter().map(|a| a.name.as_str());
+                if let Some(similar) = suggest(&argument, names) {
+                    err = err.with_help(format!("a parameter with a similar name exists: '{}'", similar));
+                }
+                return Err(err);
+            };
+            if values[i].is_some() {
+                return Err(invalid(
+                    format!("argument '{}' is given more than once", argument),
+                    ErrorCode::InvalidArgument,
+                ));
+            }
+            values[i] = Some(value);
+        }
 
         let mut ctx = self.clone();
-        for (i, arg) in arguments.into_iter().enumerate() {
-            let name = arg_definitions[i].name.clone();
-            ctx.variables.insert(name, arg);
+        ctx.returned = false;
+        // functions only see their own variables and the globals
+        ctx.scopes = vec![HashMap::new()];
+        ctx.call_stack.push(Frame {
+            function: name.to_string(),
+            call_site,
+        });
+        for (parameter, value) in parameters.iter().zip(values) {
+            let value = match (value, &parameter.default) {
+                (Some(value), _) => value,
+                // defaults may refer to the parameters before them
+                (None, Some(default)) => ctx
+                    .handle_node(default)
+                    .map_err(|err| ctx.with_backtrace(err))?,
+                (None, None) => {
+                    return Err(invalid(
+                        format!("missing argument for parameter '{}'", parameter.name),
+                        ErrorCode::ArgumentCount,
+                    ))
+                }
+            };
+            ctx.declare(&parameter.name, value, false);
+        }
+        Ok(ctx)
+    }
+
+    /// attaches the current call stack, unless a deeper call already did
+    fn with_backtrace(&self, mut err: ExecutionError) -> ExecutionError {
+        if err.backtrace.is_empty() {
+            err.backtrace = self.call_stack.iter().rev().cloned().collect();
         }
-        ctx.handle_node(&*body)
+        err
     }
 
     fn collect_functions(&mut self, ast: &Ast) -> Result<(), ExecutionError> {
         for node in ast.nodes.iter().cloned() {
+            let span = node.span;
             match node.data {
                 AstNodeData::FnDeclaration {
                     name,
@@ -79,6 +280,7 @@ impl Context {
                             arguments,
                             returns,
                             body,
+                            span,
                         },
                     );
                 }
@@ -88,141 +290,532 @@ impl Context {
         Ok(())
     }
 
+    /// declares the functions of `ast` and runs its other statements in this context,
+    /// the value is the one of the last statement
+    pub fn extend(&mut self, ast: &Ast) -> Result<Value, ExecutionError> {
+        self.collect_functions(ast)?;
+        let mut value = Value::Unit;
+        for node in ast.nodes.iter() {
+            if let AstNodeData::FnDeclaration { .. } = node.data {
+                value = Value::Unit;
+                continue;
+            }
+            value = self.handle_node(node)?;
+            // a `return` outside of a function only ends its own statement
+            self.returned = false;
+        }
+        Ok(value)
+    }
+
     fn evaluate(&mut self, ast: &Ast) -> Result<(), ExecutionError> {
         for node in ast.nodes.iter() {
+            // declared by `collect_functions`
+            if let AstNodeData::FnDeclaration { .. } = node.data {
+                continue;
+            }
             self.handle_node(node)?;
+            // a `return` outside of a function only ends its own statement
+            self.returned = false;
         }
 
         Ok(())
     }
 
-    fn handle_node(&mut self, node: &AstNode) -> Result<Option<Value>, ExecutionError> {
-        let mut output = None;
-        match &node.data {
-            AstNodeData::Block { block } => {
-                for node in block.iter() {
-                    let result = self.handle_node(node)?;
-                    if matches!(node.data, AstNodeData::Return { .. }) {
-                        return Ok(result);
-                    }
-                }
+    /// declares `name` in the innermost scope
+    fn declare(&mut self, name: &str, value: Value, constant: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), Variable { value, constant });
+        }
+    }
+
+    /// the innermost variable `name` of the running function
+    fn local(&mut self, name: &str) -> Option<&mut Variable> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name))
+    }
+
+    /// the statements of a block in its own scope, the scope is left by the caller
+    fn handle_block(
+        &mut self,
+        block: &[AstNode],
+        tail: Option<&AstNode>,
+    ) -> Result<Value, ExecutionError> {
+        for node in block {
+            let result = self.handle_node(node)?;
+            if self.returned {
+                return Ok(result);
             }
-            AstNodeData::Wrap { wrap } => output = self.handle_node(&*wrap)?,
-            AstNodeData::FnCall { name, arguments } => {
-                let mut ctx = self.clone();
-                let mut args = Vec::new();
-                for arg in arguments {
-                    let Some(value) = ctx.handle_node(&arg)? else {
-                        return Err(ExecutionError::new(
-                            arg.index,
-                            format!("invalid function argument: {}", name),
-                        ));
-                    };
-                    args.push(value);
-                }
-                output = self.call_function(&name, args).map_err(|mut err| {
-                    err.at = node.index;
-                    err
-                })?;
-            }
-            AstNodeData::VarDeclaration { name, value } => {
-                let mut ctx = self.clone();
-                let Some(value) = ctx.handle_node(&*value)? else {
-                    return Err(ExecutionError::new(
-                        node.index,
-                        format!("invalid var declaration, value cannot be None"),
-                    ));
-                };
-                let name = name.clone();
-                self.variables.insert(name, value);
-            }
-            AstNodeData::VarAssign { name, value } => {
-                let mut ctx = self.clone();
-                let Some(value) = ctx.handle_node(&*value)? else {
-                    return Err(ExecutionError::new(
-                        node.index,
-                        format!("invalid var declaration, value cannot be None"),
-                    ));
-                };
-                // let name = name.clone();
-                // self.variables.insert(name, value);
-                let Some(var) = self.variables.get_mut(name) else {
-                    return Err(ExecutionError::new(
-                        node.index,
-                        format!("cannot assign to: '{}', variable is not declared", name),
-                    ));
-                };
-                *var = value;
+        }
+        match tail {
+            Some(tail) => self.handle_node(tail),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    /// statements evaluate to `Value::Unit`,
+    /// after a `return` the returned value is passed up without evaluating anything else.
+    /// every node is evaluated by its own method, so the frames of the recursion stay small
+    fn handle_node(&mut self, node: &AstNode) -> Result<Value, ExecutionError> {
+        match &node.data {
+            AstNodeData::Block { block, tail } => {
+                self.scopes.push(HashMap::new());
+                let result = self.handle_block(block, tail.as_deref());
+                self.scopes.pop();
+                result
             }
+            AstNodeData::Wrap { wrap } => self.handle_node(wrap),
+            AstNodeData::IfStatement {
+                condition,
+                when,
+                unless,
+            } => self.handle_if(condition, when, unless.as_deref()),
+            AstNodeData::FnCall { name, arguments } => self.handle_call(node, name, arguments),
+            AstNodeData::VarDeclaration {
+                name,
+                value,
+                constant,
+            } => self.handle_declaration(node, name, value, *constant),
+            AstNodeData::VarAssign { name, value } => self.handle_assign(node, name, value),
+            AstNodeData::FnDeclaration { name, .. } => Err(nested_function(name, node.span)),
             AstNodeData::BinaryOperation {
                 operator,
+                operator_span,
                 left,
                 right,
-            } => {
-                let Some(lhs) = self.handle_node(&*left)? else {
-                    return Err(ExecutionError::new(
-                        left.index,
-                        format!("left hand side cannot be evaluated"),
-                    ));
-                };
-                let Some(rhs) = self.handle_node(&*right)? else {
-                    return Err(ExecutionError::new(
-                        right.index,
-                        format!("right hand side cannot be evaluated"),
-                    ));
-                };
-                match operator {
-                    BinaryOperator::Add => output = lhs.add(rhs.clone()),
-                    BinaryOperator::Sub => output = lhs.sub(rhs.clone()),
-                    BinaryOperator::Mul => output = lhs.mul(rhs.clone()),
-                    BinaryOperator::Div => output = lhs.div(rhs.clone()),
-                }
-                if output.is_none() {
-                    return Err(ExecutionError::new(
-                        right.index,
-                        format!(
-                            "could not apply binary operation from: {:?} to: {:?}",
-                            lhs, rhs
-                        ),
-                    ));
-                }
-                // TODO: impl math based on operator
-            }
+            } => self.handle_binary(*operator, *operator_span, left, right),
+            AstNodeData::Not { value } => self.handle_not(node, value),
             AstNodeData::Return { value } => {
-                let mut ctx = self.clone();
-                if let Some(value) = value {
-                    output = ctx.handle_node(&*value)?;
-                }
-            }
-            AstNodeData::Identifier { value } => {
-                // output = self.variables.get(value.as_str()).cloned()
-                match self.variables.get(value.as_str()).cloned() {
-                    Some(o) => output = Some(o),
-                    None => {
-                        return Err(ExecutionError::new(
-                            node.index,
-                            format!("variable: '{}' is not declared", value,),
-                        ))
-                    }
-                }
+                let output = match value {
+                    Some(value) => self.handle_node(value)?,
+                    None => Value::Unit,
+                };
+                self.returned = true;
+                Ok(output)
             }
+            AstNodeData::Identifier { value } => self.handle_identifier(node, value),
             AstNodeData::Data { data } => match data {
-                Data::Base(b) => output = Some(Value::Data(b.clone())),
-                Data::Array(a) => {
-                    let mut array = Vec::new();
-                    for node in a {
-                        let mut ctx = self.clone();
-                        let Some(value) = ctx.handle_node(&node)? else {
-                            return Err(ExecutionError::new(node.index, format!("could not evaluate")));
-                        };
-                        array.push(value);
+                Data::Base(b) => Ok(Value::Data(b.clone())),
+                Data::Array(a) => self.handle_array(a),
+                Data::Repeat { element, count } => {
+                    let value = self.handle_node(element)?;
+                    if self.returned {
+                        return Ok(value);
                     }
-                    output = Some(Value::Array(array));
+                    Ok(Value::Array(vec![value; *count]))
                 }
             },
+            AstNodeData::Interpolation { parts } => self.handle_interpolation(node, parts),
+            AstNodeData::Invalid { error } => Err(ExecutionError::new(
+                node.span,
+                format!("cannot execute invalid syntax: {}", error.cause),
+            )),
+        }
+    }
+
+    fn handle_if(
+        &mut self,
+        condition: &AstNode,
+        when: &AstNode,
+        unless: Option<&AstNode>,
+    ) -> Result<Value, ExecutionError> {
+        let value = self.handle_node(condition)?;
+        if self.returned {
+            return Ok(value);
+        }
+        let Value::Data(DataType::Bool(condition_met)) = value else {
+            return Err(ExecutionError::new(
+                condition.span,
+                format!("if condition must be `Bool`, found `{}`", value.type_name()),
+            )
+            .with_code(ErrorCode::TypeMismatch));
+        };
+        match (condition_met, unless) {
+            (true, _) => self.handle_node(when),
+            (false, Some(unless)) => self.handle_node(unless),
+            (false, None) => Ok(Value::Unit),
+        }
+    }
+
+    fn handle_call(
+        &mut self,
+        node: &AstNode,
+        name: &str,
+        arguments: &[CallArgument],
+    ) -> Result<Value, ExecutionError> {
+        let mut args = Vec::new();
+        let mut named = Vec::new();
+        for arg in arguments {
+            let value = self.handle_node(&arg.value)?;
+            if self.returned {
+                return Ok(value);
+            }
+            match &arg.name {
+                Some(name) => named.push((name.clone(), value)),
+                None => args.push(value),
+            }
+        }
+        self.call_function(name, args, named, Some(node.span))
+    }
+
+    fn handle_declaration(
+        &mut self,
+        node: &AstNode,
+        name: &str,
+        value: &AstNode,
+        constant: bool,
+    ) -> Result<Value, ExecutionError> {
+        let value = self.handle_node(value)?;
+        if self.returned {
+            return Ok(value);
+        }
+        // outside of functions and blocks variables are globals
+        if self.scopes.is_empty() {
+            self.set_global(name, value, constant, Some(node.span))?;
+        } else if self.local(name).is_some_and(|var| var.constant) {
+            return Err(constant_assignment(name, node.span));
+        } else {
+            self.declare(name, value, constant);
+        }
+        Ok(Value::Unit)
+    }
+
+    fn handle_assign(
+        &mut self,
+        node: &AstNode,
+        name: &str,
+        value: &AstNode,
+    ) -> Result<Value, ExecutionError> {
+        let value = self.handle_node(value)?;
+        if self.returned {
+            return Ok(value);
+        }
+        if let Some(var) = self.local(name) {
+            if var.constant {
+                return Err(constant_assignment(name, node.span));
+            }
+            var.value = value;
+        } else if self.globals.borrow().contains_key(name) {
+            self.set_global(name, value, false, Some(node.span))?;
+        } else {
+            return Err(ExecutionError::new(
+                node.span,
+                format!("cannot assign to: '{}', variable is not declared", name),
+            )
+            .with_code(ErrorCode::UndeclaredVariable)
+            .with_help(format!("declare it first: 'let {} = ...'", name)));
+        }
+        Ok(Value::Unit)
+    }
+
+    fn handle_binary(
+        &mut self,
+        operator: BinaryOperator,
+        operator_span: Span,
+        left: &AstNode,
+        right: &AstNode,
+    ) -> Result<Value, ExecutionError> {
+        let lhs = self.handle_node(left)?;
+        if self.returned {
+            return Ok(lhs);
+        }
+        // `&&` and `||` only evaluate the right side if needed
+        match (operator, &lhs) {
+            (BinaryOperator::And, Value::Data(DataType::Bool(false)))
+            | (BinaryOperator::Or, Value::Data(DataType::Bool(true))) => {
+                return Ok(lhs);
+            }
             _ => (),
         }
+        let rhs = self.handle_node(right)?;
+        if self.returned {
+            return Ok(rhs);
+        }
+        apply(operator, &lhs, rhs.clone()).map_err(|error| {
+            let operands = Operands {
+                operator,
+                operator_span,
+                left,
+                right,
+                lhs: &lhs,
+                rhs: &rhs,
+            };
+            operands.error(error)
+        })
+    }
+
+    fn handle_not(&mut self, node: &AstNode, value: &AstNode) -> Result<Value, ExecutionError> {
+        let operand = self.handle_node(value)?;
+        if self.returned {
+            return Ok(operand);
+        }
+        operand.not().map_err(|error| {
+            // the type of the failing element, not of the whole array
+            let found = match error {
+                ArithmeticError::InvalidOperands { lhs, .. } => lhs,
+                _ => operand.type_name(),
+            };
+            ExecutionError::new(node.span, format!("cannot apply `!` to `{}`", found))
+                .with_code(ErrorCode::InvalidOperands)
+                .with_note("`!` only negates `Bool` values".to_string())
+        })
+    }
+
+    fn handle_identifier(&mut self, node: &AstNode, name: &str) -> Result<Value, ExecutionError> {
+        if let Some(value) = self
+            .local(name)
+            .map(|var| var.value.clone())
+            .or_else(|| self.global(name))
+        {
+            return Ok(value);
+        }
+        let mut err = ExecutionError::new(node.span, format!("variable: '{}' is not declared", name))
+            .with_code(ErrorCode::UndeclaredVariable);
+        let globals = self.globals.borrow();
+        let locals = self.scopes.iter().flat_map(|scope| scope.keys());
+        let names = locals.chain(globals.keys()).map(|k| k.as_str());
+        if let Some(similar) = suggest(name, names) {
+            err = err.with_help(format!("a variable with a similar name exists: '{}'", similar));
+        }
+        Err(err)
+    }
+
+    fn handle_array(&mut self, elements: &[AstNode]) -> Result<Value, ExecutionError> {
+        let mut array = Vec::new();
+        for node in elements {
+            let value = self.handle_node(node)?;
+            if self.returned {
+                return Ok(value);
+            }
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn handle_interpolation(
+        &mut self,
+        node: &AstNode,
+        parts: &[AstNode],
+    ) -> Result<Value, ExecutionError> {
+        let mut string = String::new();
+        for part in parts {
+            let value = self.handle_node(part)?;
+            if self.returned {
+                return Ok(value);
+            }
+            string.push_str(&value.to_string());
+            if string.len() > MAX_LENGTH {
+                return Err(ExecutionError::new(
+                    node.span,
+                    "the interpolated string is too large".to_string(),
+                )
+                .with_code(ErrorCode::TooLarge)
+                .with_note(format!("strings are limited to {} bytes", MAX_LENGTH)));
+            }
+        }
+        Ok(Value::Data(DataType::String(string)))
+    }
+}
+
+/// evaluates a binary operation whose operands are already evaluated
+fn apply(operator: BinaryOperator, lhs: &Value, rhs: Value) -> Result<Value, ArithmeticError> {
+    match operator {
+        BinaryOperator::Add => lhs.add(rhs),
+        BinaryOperator::Sub => lhs.sub(rhs),
+        BinaryOperator::Mul => lhs.mul(rhs),
+        BinaryOperator::Div => lhs.div(rhs),
+        BinaryOperator::Rem => lhs.rem(rhs),
+        BinaryOperator::Pow => lhs.pow(rhs),
+        BinaryOperator::BitAnd => lhs.bit_and(rhs),
+        BinaryOperator::BitOr => lhs.bit_or(rhs),
+        BinaryOperator::BitXor => lhs.bit_xor(rhs),
+        BinaryOperator::Shl => lhs.shl(rhs),
+        BinaryOperator::Shr => lhs.shr(rhs),
+        BinaryOperator::And => lhs.and(rhs),
+        BinaryOperator::Or => lhs.or(rhs),
+        BinaryOperator::Eq => lhs.eq(rhs),
+        BinaryOperator::Ne => lhs.ne(rhs),
+        BinaryOperator::Lt => lhs.lt(rhs),
+        BinaryOperator::Le => lhs.le(rhs),
+        BinaryOperator::Gt => lhs.gt(rhs),
+        BinaryOperator::Ge => lhs.ge(rhs),
+    }
+}
+
+fn constant_assignment(name: &str, span: Span) -> ExecutionError {
+    ExecutionError::new(span, format!("cannot assign to '{}', it is a constant", name))
+        .with_code(ErrorCode::ConstantAssignment)
+        .with_help(format!("declare it with 'let {} = ...' to allow assignments", name))
+}
 
-        Ok(output)
+/// functions are declared before anything runs, which only works at the top level
+pub(super) fn nested_function(name: &str, span: Span) -> ExecutionError {
+    ExecutionError::new(
+        span,
+        format!("function '{}' must be declared at the top level", name),
+    )
+    .with_code(ErrorCode::NestedFunction)
+}
+
+fn builtin_error(name: &str, call_site: Span, error: BuiltinError) -> ExecutionError {
+    match error {
+        BuiltinError::ArgumentCount {
+            expected,
+            found,
+            signature,
+        } => ExecutionError::new(
+            call_site,
+            format!(
+                "invalid function arguments, expected {} value(s), found: {}",
+                expected, found
+            ),
+        )
+        .with_code(ErrorCode::ArgumentCount)
+        .with_note(format!("'{}' takes ({})", name, signature)),
+        BuiltinError::ArgumentType {
+            index,
+            expected,
+            found,
+        } => ExecutionError::new(
+            call_site,
+            format!(
+                "argument {} of '{}' must be {}, found `{}`",
+                index + 1,
+                name,
+                expected,
+                found
+            ),
+        )
+        .with_code(ErrorCode::TypeMismatch),
+        BuiltinError::InvalidValue(cause) => {
+            ExecutionError::new(call_site, format!("'{}': {}", name, cause))
+                .with_code(ErrorCode::InvalidValue)
+        }
+        BuiltinError::Arithmetic(error) => {
+            let code = match error {
+                ArithmeticError::InvalidOperands { .. } | ArithmeticError::NegativeCount => {
+                    ErrorCode::InvalidOperands
+                }
+                ArithmeticError::DivisionByZero => ErrorCode::DivisionByZero,
+                ArithmeticError::Overflow => ErrorCode::Overflow,
+                ArithmeticError::ShapeMismatch { .. } | ArithmeticError::Ragged => {
+                    ErrorCode::ShapeMismatch
+                }
+                ArithmeticError::TooLarge => ErrorCode::TooLarge,
+            };
+            ExecutionError::new(call_site, format!("'{}': {}", name, error)).with_code(code)
+        }
+        BuiltinError::Permission {
+            capability,
+            path: None,
+        } => ExecutionError::new(
+            call_site,
+            format!("'{}' needs the `{}` capability", name, capability),
+        )
+        .with_code(ErrorCode::PermissionDenied)
+        .with_note(format!(
+            "the host grants it with `Capabilities {{ {}: .., ..Default::default() }}`",
+            capability
+        )),
+        BuiltinError::Permission {
+            capability,
+            path: Some(path),
+        } => ExecutionError::new(
+            call_site,
+            format!("'{}' is not allowed to access '{}'", name, path),
+        )
+        .with_code(ErrorCode::PermissionDenied)
+        .with_note(format!(
+            "the path must be inside one of the `{}` directories granted by the host",
+            capability
+        )),
+        BuiltinError::Io(cause) => {
+            ExecutionError::new(call_site, format!("'{}': {}", name, cause)).with_code(ErrorCode::Io)
+        }
+    }
+}
+
+/// a failed binary operation, used to explain the failure
+struct Operands<'a> {
+    operator: BinaryOperator,
+    operator_span: Span,
+    left: &'a AstNode,
+    right: &'a AstNode,
+    lhs: &'a Value,
+    rhs: &'a Value,
+}
+impl Operands<'_> {
+    fn error(&self, error: ArithmeticError) -> ExecutionError {
+        match error {
+            ArithmeticError::InvalidOperands { lhs, rhs } => {
+                let mut err = ExecutionError::new(
+                    self.operator_span,
+                    format!("cannot apply `{}` to `{}` and `{}`", self.operator, lhs, rhs),
+                )
+                .with_code(ErrorCode::InvalidOperands)
+                .with_label(self.left.span, format!("this is `{}`", self.lhs.type_name()))
+                .with_label(self.right.span, format!("this is `{}`", self.rhs.type_name()));
+                // the operands are arrays, the error is about their elements
+                if (lhs, rhs) != (self.lhs.type_name(), self.rhs.type_name()) {
+                    err = err.with_note(format!(
+                        "applied element-wise, some elements are `{}` and `{}`",
+                        lhs, rhs
+                    ));
+                }
+                err
+            }
+            ArithmeticError::DivisionByZero => ExecutionError::new(
+                self.operator_span,
+                "attempt to divide by zero".to_string(),
+            )
+            .with_code(ErrorCode::DivisionByZero)
+            .with_label(self.right.span, "divided by this".to_string())
+            .with_note("dividing a float by zero results in inf or NaN".to_string()),
+            ArithmeticError::ShapeMismatch { lhs, rhs } => ExecutionError::new(
+                self.operator_span,
+                format!("cannot broadcast arrays of shape {:?} and {:?}", lhs, rhs),
+            )
+            .with_code(ErrorCode::ShapeMismatch)
+            .with_label(self.left.span, format!("shape {:?}", lhs))
+            .with_label(self.right.span, format!("shape {:?}", rhs))
+            .with_note(
+                "shapes are compared from their last dimension, \
+                 each pair must be equal or one of them 1"
+                    .to_string(),
+            ),
+            ArithmeticError::Ragged => {
+                let ragged = match self.lhs.shape() {
+                    None => self.left.span,
+                    Some(_) => self.right.span,
+                };
+                ExecutionError::new(
+                    self.operator_span,
+                    "cannot broadcast a ragged array".to_string(),
+                )
+                .with_code(ErrorCode::ShapeMismatch)
+                .with_label(ragged, "this array is ragged".to_string())
+                .with_note("all elements of an array must have the same shape".to_string())
+            }
+            ArithmeticError::Overflow => ExecutionError::new(
+                self.operator_span,
+                format!("integer overflow in `{}`", self.operator),
+            )
+            .with_code(ErrorCode::Overflow)
+            .with_note(format!(
+                "integers range from {} to {}",
+                i64::MIN,
+                i64::MAX
+            )),
+            ArithmeticError::NegativeCount => ExecutionError::new(
+                self.operator_span,
+                "cannot repeat a string a negative number of times".to_string(),
+            )
+            .with_code(ErrorCode::InvalidOperands)
+            .with_note("the repetition count must be zero or positive".to_string()),
+            ArithmeticError::TooLarge => ExecutionError::new(
+                self.operator_span,
+                format!("the result of `{}` is too large", self.operator),
+            )
+            .with_code(ErrorCode::TooLarge)
+            .with_note(format!("strings are limited to {} bytes", MAX_LENGTH)),
+        }
     }
 }
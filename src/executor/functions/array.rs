@@ -0,0 +1,450 @@
+//! functions on arrays.
+//! arguments are copies, so functions that change an array return the changed array
+//! and leave the original as it is, e.g. `let a = push(a, 1);`
+
+use std::cmp::Ordering;
+
+use crate::data::{ArithmeticError, DataType, MAX_LENGTH};
+
+use super::{array, int, string, BuiltinError, Function, Host, Parameter, Type, Value};
+
+pub(super) fn functions() -> Vec<&'static dyn Function> {
+    vec![
+        &Len {},
+        &Push {},
+        &Pop {},
+        &Insert {},
+        &Remove {},
+        &Concat {},
+        &Reverse {},
+        &Sort {},
+        &Contains {},
+        &IndexOf {},
+        &SUM,
+        &PRODUCT,
+        &MIN,
+        &MAX,
+        &Zip {},
+        &Flatten {},
+        &Range {},
+    ]
+}
+
+/// the parameters of the functions of a single array
+const ARRAY: &[Parameter] = &[Parameter::required("array", Type::Array)];
+
+fn int_value(n: usize) -> Value {
+    Value::Data(DataType::Int(n as i64))
+}
+
+/// `index` as a position in `array`, which may be one past the end if `end` is allowed
+fn position(array: &[Value], index: i64, end: bool) -> Result<usize, BuiltinError> {
+    let len = array.len() + usize::from(end);
+    match usize::try_from(index) {
+        Ok(i) if i < len => Ok(i),
+        _ => Err(BuiltinError::InvalidValue(format!(
+            "index {} is out of bounds for an array of length {}",
+            index,
+            array.len()
+        ))),
+    }
+}
+
+/// the number of elements of an array or characters of a string
+struct Len {}
+impl Function for Len {
+    fn name(&self) -> &'static str {
+        "len"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("value", Type::Sequence)];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        match &args[0] {
+            Value::Array(array) => Ok(int_value(array.len())),
+            Value::Data(DataType::String(s)) => Ok(int_value(s.chars().count())),
+            other => Err(BuiltinError::ArgumentType {
+                index: 0,
+                expected: "an array or a string",
+                found: other.type_name(),
+            }),
+        }
+    }
+}
+
+/// `push(array, value)` appends `value`
+struct Push {}
+impl Function for Push {
+    fn name(&self) -> &'static str {
+        "push"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("array", Type::Array),
+            Parameter::required("value", Type::Any),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut array = array(&args, 0)?.to_vec();
+        array.push(args[1].clone());
+        Ok(Value::Array(array))
+    }
+}
+
+/// the last element, use `remove(array, len(array) - 1)` for the array without it
+struct Pop {}
+impl Function for Pop {
+    fn name(&self) -> &'static str {
+        "pop"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        array(&args, 0)?
+            .last()
+            .cloned()
+            .ok_or_else(|| BuiltinError::InvalidValue("cannot pop from an empty array".to_string()))
+    }
+}
+
+/// `insert(array, index, value)`, an index equal to the length appends
+struct Insert {}
+impl Function for Insert {
+    fn name(&self) -> &'static str {
+        "insert"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("array", Type::Array),
+            Parameter::required("index", Type::Int),
+            Parameter::required("value", Type::Any),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut array = array(&args, 0)?.to_vec();
+        let index = position(&array, int(&args, 1)?, true)?;
+        array.insert(index, args[2].clone());
+        Ok(Value::Array(array))
+    }
+}
+
+/// `remove(array, index)`, the array without the element at `index`
+struct Remove {}
+impl Function for Remove {
+    fn name(&self) -> &'static str {
+        "remove"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("array", Type::Array),
+            Parameter::required("index", Type::Int),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut array = array(&args, 0)?.to_vec();
+        let index = position(&array, int(&args, 1)?, false)?;
+        array.remove(index);
+        Ok(Value::Array(array))
+    }
+}
+
+/// the elements of two or more arrays, one after the other
+struct Concat {}
+impl Function for Concat {
+    fn name(&self) -> &'static str {
+        "concat"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("a", Type::Array),
+            Parameter::required("b", Type::Array),
+            Parameter::variadic("rest", Type::Array),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut result = Vec::new();
+        for i in 0..args.len() {
+            result.extend_from_slice(array(&args, i)?);
+        }
+        Ok(Value::Array(result))
+    }
+}
+
+struct Reverse {}
+impl Function for Reverse {
+    fn name(&self) -> &'static str {
+        "reverse"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut array = array(&args, 0)?.to_vec();
+        array.reverse();
+        Ok(Value::Array(array))
+    }
+}
+
+/// stable, in the order of `Value::total_cmp`, so elements of any type can be sorted
+struct Sort {}
+impl Function for Sort {
+    fn name(&self) -> &'static str {
+        "sort"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut array = array(&args, 0)?.to_vec();
+        array.sort_by(Value::total_cmp);
+        Ok(Value::Array(array))
+    }
+}
+
+/// the first index of an element equal to `value`, numbers are equal if their values are
+fn index_of(array: &[Value], value: &Value) -> Option<usize> {
+    array.iter().position(|v| v.total_cmp(value).is_eq())
+}
+
+/// `contains(array, value)`, or `contains(string, pattern)` for strings
+struct Contains {}
+impl Function for Contains {
+    fn name(&self) -> &'static str {
+        "contains"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("haystack", Type::Sequence),
+            Parameter::required("needle", Type::Any),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let contains = match &args[0] {
+            Value::Array(array) => index_of(array, &args[1]).is_some(),
+            _ => string(&args, 0)?.contains(string(&args, 1)?),
+        };
+        Ok(Value::Data(DataType::Bool(contains)))
+    }
+}
+
+/// `-1` if there is no such element
+struct IndexOf {}
+impl Function for IndexOf {
+    fn name(&self) -> &'static str {
+        "index_of"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("array", Type::Array),
+            Parameter::required("value", Type::Any),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let index = index_of(array(&args, 0)?, &args[1]).map_or(-1, |i| i as i64);
+        Ok(Value::Data(DataType::Int(index)))
+    }
+}
+
+/// combines the elements with an arithmetic operator, so arrays of arrays combine element-wise
+struct Fold {
+    name: &'static str,
+    /// the result for an empty array
+    initial: i64,
+    f: fn(&Value, Value) -> Result<Value, ArithmeticError>,
+}
+impl Function for Fold {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut elements = array(&args, 0)?.iter();
+        let Some(first) = elements.next() else {
+            return Ok(Value::Data(DataType::Int(self.initial)));
+        };
+        let mut result = first.clone();
+        for element in elements {
+            result = (self.f)(&result, element.clone())?;
+        }
+        Ok(result)
+    }
+}
+
+const SUM: Fold = Fold {
+    name: "sum",
+    initial: 0,
+    f: Value::add,
+};
+const PRODUCT: Fold = Fold {
+    name: "product",
+    initial: 1,
+    f: Value::mul,
+};
+
+/// the smallest or largest element in the order of `sort`
+struct Extremum {
+    name: &'static str,
+    pick: Ordering,
+}
+impl Function for Extremum {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut elements = array(&args, 0)?.iter();
+        let Some(mut result) = elements.next() else {
+            return Err(BuiltinError::InvalidValue(format!(
+                "'{}' of an empty array",
+                self.name
+            )));
+        };
+        for element in elements {
+            if element.total_cmp(result) == self.pick {
+                result = element;
+            }
+        }
+        Ok(result.clone())
+    }
+}
+
+const MIN: Extremum = Extremum {
+    name: "min",
+    pick: Ordering::Less,
+};
+const MAX: Extremum = Extremum {
+    name: "max",
+    pick: Ordering::Greater,
+};
+
+/// pairs of elements at the same index, stops at the end of the shorter array
+struct Zip {}
+impl Function for Zip {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("a", Type::Array),
+            Parameter::required("b", Type::Array),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let pairs = array(&args, 0)?
+            .iter()
+            .zip(array(&args, 1)?)
+            .map(|(l, r)| Value::Array(vec![l.clone(), r.clone()]))
+            .collect();
+        Ok(Value::Array(pairs))
+    }
+}
+
+/// removes one level of nesting, elements that are not arrays are kept as they are
+struct Flatten {}
+impl Function for Flatten {
+    fn name(&self) -> &'static str {
+        "flatten"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        ARRAY
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut result = Vec::new();
+        for element in array(&args, 0)? {
+            match element {
+                Value::Array(inner) => result.extend_from_slice(inner),
+                other => result.push(other.clone()),
+            }
+        }
+        Ok(Value::Array(result))
+    }
+}
+
+/// `range(end)`, `range(start, end)` or `range(start, end, step)`,
+/// the integers from `start` up to but excluding `end`
+struct Range {}
+impl Function for Range {
+    fn name(&self) -> &'static str {
+        "range"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("start", Type::Int),
+            Parameter::optional("end", Type::Int),
+            Parameter::default("step", Type::Int, || Value::Data(DataType::Int(1))),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let (start, end) = match args.len() {
+            1 => (0, int(&args, 0)?),
+            _ => (int(&args, 0)?, int(&args, 1)?),
+        };
+        let step = match args.get(2) {
+            Some(_) => int(&args, 2)?,
+            None => 1,
+        };
+        if step == 0 {
+            return Err(BuiltinError::InvalidValue("the step cannot be zero".to_string()));
+        }
+        // i128 holds the distance between any two `Int`s
+        let (start, end, step) = (start as i128, end as i128, step as i128);
+        let length = match step > 0 {
+            true => (end - start + step - 1) / step,
+            false => (end - start + step + 1) / step,
+        };
+        if length > MAX_LENGTH as i128 {
+            return Err(ArithmeticError::TooLarge.into());
+        }
+        let mut values = Vec::with_capacity(length.max(0) as usize);
+        let mut n = start;
+        while (step > 0 && n < end) || (step < 0 && n > end) {
+            values.push(Value::Data(DataType::Int(n as i64)));
+            n += step;
+        }
+        Ok(Value::Array(values))
+    }
+}
@@ -0,0 +1,194 @@
+//! functions that access the host.
+//! each needs a capability, calls are rejected before the arguments are checked
+//! if the `Capabilities` of the executor do not grant it
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::data::DataType;
+
+use super::{string, BuiltinError, Capability, Function, Host, Parameter, Type, Value};
+
+pub(super) fn functions() -> Vec<&'static dyn Function> {
+    vec![
+        &Print {},
+        &ReadLine {},
+        &ReadFile {},
+        &WriteFile {},
+        &Env {},
+        &Now {},
+    ]
+}
+
+fn io_error(path: &str, e: io::Error) -> BuiltinError {
+    BuiltinError::Io(format!("'{}': {}", path, e))
+}
+
+/// writes its arguments and a line break to the output of the executor, stdout by default
+struct Print {}
+impl Function for Print {
+    fn name(&self) -> &'static str {
+        "print"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::variadic("values", Type::Any)];
+        PARAMETERS
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::Stdout)
+    }
+
+    fn call(&self, args: Vec<Value>, host: &Host) -> Result<Value, BuiltinError> {
+        let mut string = String::new();
+        for a in args {
+            string.push_str(&a.to_string());
+        }
+        writeln!(host.output.borrow_mut(), "{}", string)
+            .map_err(|e| BuiltinError::Io(e.to_string()))?;
+        Ok(Value::Unit)
+    }
+}
+
+/// a line from stdin without the line break, an empty string at the end of input
+struct ReadLine {}
+impl Function for ReadLine {
+    fn name(&self) -> &'static str {
+        "read_line"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::Stdin)
+    }
+
+    fn call(&self, _: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut line = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| BuiltinError::Io(e.to_string()))?;
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Value::Data(DataType::String(line)))
+    }
+}
+
+/// the contents of a UTF-8 text file
+struct ReadFile {}
+impl Function for ReadFile {
+    fn name(&self) -> &'static str {
+        "read_file"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("path", Type::String)];
+        PARAMETERS
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::FsRead)
+    }
+
+    fn call(&self, args: Vec<Value>, host: &Host) -> Result<Value, BuiltinError> {
+        let path = string(&args, 0)?;
+        if !host.capabilities.may_read(Path::new(path)) {
+            return Err(BuiltinError::Permission {
+                capability: Capability::FsRead,
+                path: Some(path.to_string()),
+            });
+        }
+        let contents = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+        Ok(Value::Data(DataType::String(contents)))
+    }
+}
+
+/// `write_file(path, contents)` creates or replaces the file
+struct WriteFile {}
+impl Function for WriteFile {
+    fn name(&self) -> &'static str {
+        "write_file"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("path", Type::String),
+            Parameter::required("contents", Type::String),
+        ];
+        PARAMETERS
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::FsWrite)
+    }
+
+    fn call(&self, args: Vec<Value>, host: &Host) -> Result<Value, BuiltinError> {
+        let path = string(&args, 0)?;
+        if !host.capabilities.may_write(Path::new(path)) {
+            return Err(BuiltinError::Permission {
+                capability: Capability::FsWrite,
+                path: Some(path.to_string()),
+            });
+        }
+        fs::write(path, string(&args, 1)?).map_err(|e| io_error(path, e))?;
+        Ok(Value::Unit)
+    }
+}
+
+/// the value of an environment variable, `()` if it is not set
+struct Env {}
+impl Function for Env {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("name", Type::String)];
+        PARAMETERS
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::Env)
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        match std::env::var(string(&args, 0)?) {
+            Ok(value) => Ok(Value::Data(DataType::String(value))),
+            Err(_) => Ok(Value::Unit),
+        }
+    }
+}
+
+/// the seconds since the Unix epoch as a `Float`
+struct Now {}
+impl Function for Now {
+    fn name(&self) -> &'static str {
+        "now"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        Some(Capability::Clock)
+    }
+
+    fn call(&self, _: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| BuiltinError::Io(e.to_string()))?
+            .as_secs_f64();
+        Ok(Value::Data(DataType::Float(seconds)))
+    }
+}
@@ -0,0 +1,335 @@
+//! the `math::` functions and constants.
+//! numbers may be `Int` or `Float`, arrays are processed element-wise
+//! and functions of several numbers broadcast their arguments like the arithmetic operators
+
+use std::f64::consts;
+
+use crate::data::{ArithmeticError, DataType};
+
+use super::{BuiltinError, Function, Host, Parameter, Type, Value};
+
+pub(super) fn functions() -> Vec<&'static dyn Function> {
+    vec![
+        &SIN,
+        &COS,
+        &TAN,
+        &ASIN,
+        &ACOS,
+        &ATAN,
+        &ATAN2,
+        &SQRT,
+        &EXP,
+        &LN,
+        &Log {},
+        &POW,
+        &Abs {},
+        &MIN,
+        &MAX,
+        &Clamp {},
+        &Floor {},
+        &Ceil {},
+        &Round {},
+    ]
+}
+
+pub(super) fn constants() -> Vec<(&'static str, Value)> {
+    vec![
+        ("math::PI", Value::Data(DataType::Float(consts::PI))),
+        ("math::E", Value::Data(DataType::Float(consts::E))),
+    ]
+}
+
+/// the number as a float, `None` for other types
+fn float(d: &DataType) -> Option<f64> {
+    match d {
+        DataType::Float(n) => Some(*n),
+        DataType::Int(n) => Some(*n as f64),
+        _ => None,
+    }
+}
+
+/// the parameters of the functions of a single number
+const NUMBER: &[Parameter] = &[Parameter::required("x", Type::Numbers)];
+
+/// applies `f` to every number in `value`
+fn element_wise(
+    value: &Value,
+    f: &dyn Fn(&DataType) -> Result<DataType, ArithmeticError>,
+) -> Result<Value, ArithmeticError> {
+    match value {
+        Value::Data(d) => f(d).map(Value::Data),
+        Value::Array(array) => array
+            .iter()
+            .map(|v| element_wise(v, f))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Unit => Err(ArithmeticError::InvalidOperands {
+            lhs: "Unit",
+            rhs: "Unit",
+        }),
+    }
+}
+
+/// only reached if a parameter accepts more than its function handles
+fn invalid(lhs: &DataType, rhs: &DataType) -> ArithmeticError {
+    ArithmeticError::InvalidOperands {
+        lhs: lhs.type_name(),
+        rhs: rhs.type_name(),
+    }
+}
+
+/// a function of one number, the result is always a `Float`
+pub(super) struct Unary {
+    name: &'static str,
+    f: fn(f64) -> f64,
+}
+impl Function for Unary {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        NUMBER
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let f = |d: &DataType| float(d).map(|n| DataType::Float((self.f)(n))).ok_or(invalid(d, d));
+        Ok(element_wise(&args[0], &f)?)
+    }
+}
+
+pub(super) const SIN: Unary = Unary { name: "math::sin", f: f64::sin };
+const COS: Unary = Unary { name: "math::cos", f: f64::cos };
+const TAN: Unary = Unary { name: "math::tan", f: f64::tan };
+const ASIN: Unary = Unary { name: "math::asin", f: f64::asin };
+const ACOS: Unary = Unary { name: "math::acos", f: f64::acos };
+const ATAN: Unary = Unary { name: "math::atan", f: f64::atan };
+/// negative numbers result in `NaN`
+const SQRT: Unary = Unary { name: "math::sqrt", f: f64::sqrt };
+const EXP: Unary = Unary { name: "math::exp", f: f64::exp };
+/// the natural logarithm
+const LN: Unary = Unary { name: "math::ln", f: f64::ln };
+
+/// a function of two numbers, broadcast over arrays
+struct Binary {
+    name: &'static str,
+    parameters: &'static [Parameter],
+    f: fn(&DataType, DataType) -> Result<DataType, ArithmeticError>,
+}
+impl Function for Binary {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        self.parameters
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(args[0].broadcast(&args[1], self.f)?)
+    }
+}
+
+/// `math::atan2(y, x)`, the angle of the point `(x, y)`
+const ATAN2: Binary = Binary {
+    name: "math::atan2",
+    parameters: &[
+        Parameter::required("y", Type::Numbers),
+        Parameter::required("x", Type::Numbers),
+    ],
+    f: |y, x| match (float(y), float(&x)) {
+        (Some(y), Some(x)) => Ok(DataType::Float(y.atan2(x))),
+        _ => Err(invalid(y, &x)),
+    },
+};
+/// like `**`, integers stay integers unless the exponent is negative
+const POW: Binary = Binary {
+    name: "math::pow",
+    parameters: &[
+        Parameter::required("base", Type::Numbers),
+        Parameter::required("exponent", Type::Numbers),
+    ],
+    f: DataType::pow,
+};
+/// the smaller of two numbers, a `Float` if either is one
+fn min(lhs: &DataType, rhs: DataType) -> Result<DataType, ArithmeticError> {
+    match (lhs, &rhs) {
+        (DataType::Int(l), DataType::Int(r)) => Ok(DataType::Int(*l.min(r))),
+        (l, r) => match (float(l), float(r)) {
+            (Some(l), Some(r)) => Ok(DataType::Float(l.min(r))),
+            _ => Err(invalid(lhs, &rhs)),
+        },
+    }
+}
+/// the larger of two numbers, a `Float` if either is one
+fn max(lhs: &DataType, rhs: DataType) -> Result<DataType, ArithmeticError> {
+    match (lhs, &rhs) {
+        (DataType::Int(l), DataType::Int(r)) => Ok(DataType::Int(*l.max(r))),
+        (l, r) => match (float(l), float(r)) {
+            (Some(l), Some(r)) => Ok(DataType::Float(l.max(r))),
+            _ => Err(invalid(lhs, &rhs)),
+        },
+    }
+}
+
+/// the smallest or largest of two or more numbers, compared element-wise
+struct Extremum {
+    name: &'static str,
+    f: fn(&DataType, DataType) -> Result<DataType, ArithmeticError>,
+}
+impl Function for Extremum {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("a", Type::Numbers),
+            Parameter::required("b", Type::Numbers),
+            Parameter::variadic("rest", Type::Numbers),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let mut result = args[0].clone();
+        for arg in &args[1..] {
+            result = result.broadcast(arg, self.f)?;
+        }
+        Ok(result)
+    }
+}
+
+const MIN: Extremum = Extremum {
+    name: "math::min",
+    f: min,
+};
+const MAX: Extremum = Extremum {
+    name: "math::max",
+    f: max,
+};
+
+/// `math::clamp(x, low, high)`, `low` wins if it is larger than `high`
+struct Clamp {}
+impl Function for Clamp {
+    fn name(&self) -> &'static str {
+        "math::clamp"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("x", Type::Numbers),
+            Parameter::required("low", Type::Numbers),
+            Parameter::required("high", Type::Numbers),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(args[0].broadcast(&args[2], min)?.broadcast(&args[1], max)?)
+    }
+}
+
+/// `math::log(x, base)`, the base defaults to 10
+struct Log {}
+impl Function for Log {
+    fn name(&self) -> &'static str {
+        "math::log"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("x", Type::Numbers),
+            Parameter::default("base", Type::Numbers, || Value::Data(DataType::Float(10.0))),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let log = |x: &DataType, base: DataType| match (float(x), float(&base)) {
+            // the dedicated functions are exact for powers of the base
+            (Some(x), Some(10.0)) => Ok(DataType::Float(x.log10())),
+            (Some(x), Some(2.0)) => Ok(DataType::Float(x.log2())),
+            (Some(x), Some(b)) => Ok(DataType::Float(x.log(b))),
+            _ => Err(invalid(x, &base)),
+        };
+        Ok(args[0].broadcast(&args[1], log)?)
+    }
+}
+
+/// integers stay integers, `math::abs` of the smallest `Int` overflows
+struct Abs {}
+impl Function for Abs {
+    fn name(&self) -> &'static str {
+        "math::abs"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        NUMBER
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let abs = |d: &DataType| match d {
+            DataType::Int(n) => n.checked_abs().map(DataType::Int).ok_or(ArithmeticError::Overflow),
+            DataType::Float(n) => Ok(DataType::Float(n.abs())),
+            other => Err(invalid(other, other)),
+        };
+        Ok(element_wise(&args[0], &abs)?)
+    }
+}
+
+/// applies `f` to floats, integers are already whole and returned as they are
+fn round_with(args: Vec<Value>, f: fn(f64) -> f64) -> Result<Value, BuiltinError> {
+    let round = |d: &DataType| match d {
+        DataType::Float(n) => Ok(DataType::Float(f(*n))),
+        DataType::Int(n) => Ok(DataType::Int(*n)),
+        other => Err(invalid(other, other)),
+    };
+    Ok(element_wise(&args[0], &round)?)
+}
+
+/// rounds half away from zero
+pub(super) struct Round {}
+impl Function for Round {
+    fn name(&self) -> &'static str {
+        "math::round"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        NUMBER
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        round_with(args, f64::round)
+    }
+}
+
+pub(super) struct Floor {}
+impl Function for Floor {
+    fn name(&self) -> &'static str {
+        "math::floor"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        NUMBER
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        round_with(args, f64::floor)
+    }
+}
+
+pub(super) struct Ceil {}
+impl Function for Ceil {
+    fn name(&self) -> &'static str {
+        "math::ceil"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        NUMBER
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        round_with(args, f64::ceil)
+    }
+}
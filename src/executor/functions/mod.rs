@@ -0,0 +1,399 @@
+mod array;
+mod io;
+mod math;
+mod string;
+
+use std::fmt;
+
+use crate::data::{ArithmeticError, DataType};
+
+use super::host::{Capability, Host};
+use super::Value;
+
+fn inbuilt() -> Vec<&'static dyn Function> {
+    let mut functions: Vec<&'static dyn Function> = vec![
+        &Str {},
+        &ToInt {},
+        &ToFloat {},
+        // unqualified names from before the `math` module
+        &Alias { name: "sin", function: &math::SIN },
+        &Alias { name: "round", function: &math::Round {} },
+        &Alias { name: "floor", function: &math::Floor {} },
+        &Alias { name: "ceil", function: &math::Ceil {} },
+    ];
+    functions.extend(io::functions());
+    functions.extend(math::functions());
+    functions.extend(array::functions());
+    functions.extend(string::functions());
+    functions
+}
+
+pub fn inbuilt_names() -> Vec<&'static str> {
+    inbuilt().into_iter().map(|f| f.name()).collect()
+}
+
+/// `None` if there is no built-in function called `name`
+pub fn call_inbuilt(
+    name: &str,
+    arguments: Vec<Value>,
+    host: &Host,
+) -> Option<Result<Value, BuiltinError>> {
+    let f = inbuilt().into_iter().find(|f| f.name() == name)?;
+    if let Some(capability) = f.capability() {
+        if !host.capabilities.allows(capability) {
+            return Some(Err(BuiltinError::Permission {
+                capability,
+                path: None,
+            }));
+        }
+    }
+    Some(validate(f.parameters(), arguments).and_then(|args| f.call(args, host)))
+}
+
+/// values every script starts with, e.g. `math::PI`
+pub fn constants() -> Vec<(&'static str, Value)> {
+    math::constants()
+}
+
+/// why a call to a built-in function failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuiltinError {
+    /// `expected` describes the accepted counts, e.g. `1` or `2 to 3`,
+    /// `signature` lists the parameters
+    ArgumentCount {
+        expected: String,
+        found: usize,
+        signature: String,
+    },
+    /// the argument at `index`, or one of its elements, has a type the function does not accept
+    ArgumentType {
+        index: usize,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// the arguments have valid types, but no result for their values
+    InvalidValue(String),
+    /// the host did not grant `capability`, or did not grant it for `path`
+    Permission {
+        capability: Capability,
+        path: Option<String>,
+    },
+    /// reading or writing failed, e.g. a missing file
+    Io(String),
+    Arithmetic(ArithmeticError),
+}
+impl From<ArithmeticError> for BuiltinError {
+    fn from(error: ArithmeticError) -> Self {
+        Self::Arithmetic(error)
+    }
+}
+
+/// what a parameter accepts
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Type {
+    Any,
+    Int,
+    String,
+    Array,
+    /// `Int` or `Float`, or an array of them
+    Numbers,
+    /// `String`, `Int`, `Float` or `Bool`
+    Scalar,
+    /// an array or a string
+    Sequence,
+    /// an array of strings
+    Strings,
+}
+impl Type {
+    /// `Err` with the type of the rejected value, or of the rejected element of an array
+    fn check(self, value: &Value) -> Result<(), &'static str> {
+        let accepted = match (self, value) {
+            (Type::Any, _) | (Type::Array, Value::Array(_)) | (Type::Scalar, Value::Data(_)) => true,
+            (Type::Int, Value::Data(DataType::Int(_))) => true,
+            (Type::String | Type::Sequence, Value::Data(DataType::String(_))) => true,
+            (Type::Sequence, Value::Array(_)) => true,
+            (Type::Numbers, Value::Data(DataType::Int(_) | DataType::Float(_))) => true,
+            (Type::Numbers, Value::Array(array)) => return array.iter().try_for_each(|v| self.check(v)),
+            (Type::Strings, Value::Array(array)) => {
+                return array.iter().try_for_each(|v| Type::String.check(v))
+            }
+            _ => false,
+        };
+        match accepted {
+            true => Ok(()),
+            false => Err(value.type_name()),
+        }
+    }
+
+    /// completes "must be ..." in error messages
+    fn description(self) -> &'static str {
+        match self {
+            Type::Any => "a value",
+            Type::Int => "an integer",
+            Type::String => "a string",
+            Type::Array => "an array",
+            Type::Numbers => "a number",
+            Type::Scalar => "a string, number or bool",
+            Type::Sequence => "an array or a string",
+            Type::Strings => "an array of strings",
+        }
+    }
+}
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Any => "Any",
+            Type::Int => "Int",
+            Type::String => "String",
+            Type::Array => "Array",
+            Type::Numbers => "Number",
+            Type::Scalar => "Scalar",
+            Type::Sequence => "Array | String",
+            Type::Strings => "[String]",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Kind {
+    Required,
+    /// may be left out, `default` is passed instead if there is one.
+    /// only optional parameters may follow
+    Optional(Option<fn() -> Value>),
+    /// takes any number of further arguments, only valid as the last parameter
+    Variadic,
+}
+
+/// a declared parameter of a built-in function
+#[derive(Copy, Clone, Debug)]
+struct Parameter {
+    name: &'static str,
+    ty: Type,
+    kind: Kind,
+}
+impl Parameter {
+    const fn required(name: &'static str, ty: Type) -> Self {
+        Self { name, ty, kind: Kind::Required }
+    }
+
+    const fn optional(name: &'static str, ty: Type) -> Self {
+        Self { name, ty, kind: Kind::Optional(None) }
+    }
+
+    const fn default(name: &'static str, ty: Type, default: fn() -> Value) -> Self {
+        Self { name, ty, kind: Kind::Optional(Some(default)) }
+    }
+
+    const fn variadic(name: &'static str, ty: Type) -> Self {
+        Self { name, ty, kind: Kind::Variadic }
+    }
+}
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Required => write!(f, "{}: {}", self.name, self.ty),
+            Kind::Optional(None) => write!(f, "{}?: {}", self.name, self.ty),
+            Kind::Optional(Some(default)) => {
+                write!(f, "{}: {} = {}", self.name, self.ty, default().repr())
+            }
+            Kind::Variadic => write!(f, "...{}: {}", self.name, self.ty),
+        }
+    }
+}
+
+/// checks the number and types of `args` against `parameters` and fills in defaults,
+/// so every built-in function is only called with arguments it accepts
+fn validate(parameters: &[Parameter], mut args: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+    let required = parameters
+        .iter()
+        .filter(|p| matches!(p.kind, Kind::Required))
+        .count();
+    let variadic = parameters
+        .last()
+        .is_some_and(|p| matches!(p.kind, Kind::Variadic));
+    let count = match variadic {
+        true => required..=usize::MAX,
+        false => required..=parameters.len(),
+    };
+    if !count.contains(&args.len()) {
+        let expected = match (count.start(), count.end()) {
+            (start, end) if start == end => start.to_string(),
+            (start, &usize::MAX) => format!("at least {}", start),
+            (start, end) => format!("{} to {}", start, end),
+        };
+        let signature: Vec<String> = parameters.iter().map(|p| p.to_string()).collect();
+        return Err(BuiltinError::ArgumentCount {
+            expected,
+            found: args.len(),
+            signature: signature.join(", "),
+        });
+    }
+    for (index, value) in args.iter().enumerate() {
+        // further arguments belong to the variadic parameter
+        let parameter = &parameters[index.min(parameters.len() - 1)];
+        parameter
+            .ty
+            .check(value)
+            .map_err(|found| BuiltinError::ArgumentType {
+                index,
+                expected: parameter.ty.description(),
+                found,
+            })?;
+    }
+    for parameter in parameters.iter().skip(args.len()) {
+        match parameter.kind {
+            Kind::Optional(Some(default)) => args.push(default()),
+            _ => break,
+        }
+    }
+    Ok(args)
+}
+
+// the argument helpers only fail if the parameters of a function do not match its body
+
+/// the argument at `index` as a string
+fn string(args: &[Value], index: usize) -> Result<&str, BuiltinError> {
+    match &args[index] {
+        Value::Data(DataType::String(s)) => Ok(s),
+        _ => Err(rejected(args, index, Type::String)),
+    }
+}
+
+/// the argument at `index` as an array
+fn array(args: &[Value], index: usize) -> Result<&[Value], BuiltinError> {
+    match &args[index] {
+        Value::Array(array) => Ok(array),
+        _ => Err(rejected(args, index, Type::Array)),
+    }
+}
+
+/// the argument at `index` as an integer
+fn int(args: &[Value], index: usize) -> Result<i64, BuiltinError> {
+    match &args[index] {
+        Value::Data(DataType::Int(n)) => Ok(*n),
+        _ => Err(rejected(args, index, Type::Int)),
+    }
+}
+
+fn rejected(args: &[Value], index: usize, expected: Type) -> BuiltinError {
+    BuiltinError::ArgumentType {
+        index,
+        expected: expected.description(),
+        found: args[index].type_name(),
+    }
+}
+
+trait Function {
+    fn name(&self) -> &'static str;
+    fn parameters(&self) -> &'static [Parameter];
+    /// the capability the function needs, checked before it is called
+    fn capability(&self) -> Option<Capability> {
+        None
+    }
+    /// only called with arguments accepted by `parameters`, with defaults filled in
+    fn call(&self, args: Vec<Value>, host: &Host) -> Result<Value, BuiltinError>;
+}
+
+/// another name for `function`
+struct Alias {
+    name: &'static str,
+    function: &'static dyn Function,
+}
+impl Function for Alias {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        self.function.parameters()
+    }
+
+    fn capability(&self) -> Option<Capability> {
+        self.function.capability()
+    }
+
+    fn call(&self, args: Vec<Value>, host: &Host) -> Result<Value, BuiltinError> {
+        self.function.call(args, host)
+    }
+}
+
+/// the text `print` would show for a value
+struct Str {}
+impl Function for Str {
+    fn name(&self) -> &'static str {
+        "str"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("value", Type::Any)];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(Value::Data(DataType::String(args[0].to_string())))
+    }
+}
+
+/// truncates towards zero, fails for floats out of the `Int` range and unparsable strings
+struct ToInt {}
+impl Function for ToInt {
+    fn name(&self) -> &'static str {
+        "int"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("value", Type::Scalar)];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let invalid = || BuiltinError::InvalidValue(format!("cannot convert {} to `Int`", args[0].repr()));
+        let int = match &args[0] {
+            Value::Data(DataType::Int(n)) => *n,
+            Value::Data(DataType::Float(n)) => float_to_int(*n).ok_or_else(invalid)?,
+            Value::Data(DataType::String(s)) => match s.trim().parse::<i64>() {
+                Ok(n) => n,
+                Err(_) => s
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(float_to_int)
+                    .ok_or_else(invalid)?,
+            },
+            Value::Data(DataType::Bool(b)) => i64::from(*b),
+            _ => return Err(rejected(&args, 0, Type::Scalar)),
+        };
+        Ok(Value::Data(DataType::Int(int)))
+    }
+}
+fn float_to_int(n: f64) -> Option<i64> {
+    // i64::MAX as f64 rounds up to 2^63, which is out of range
+    let n = n.trunc();
+    (n >= i64::MIN as f64 && n < i64::MAX as f64).then_some(n as i64)
+}
+
+struct ToFloat {}
+impl Function for ToFloat {
+    fn name(&self) -> &'static str {
+        "float"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[Parameter::required("value", Type::Scalar)];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let float = match &args[0] {
+            Value::Data(DataType::Int(n)) => *n as f64,
+            Value::Data(DataType::Float(n)) => *n,
+            Value::Data(DataType::String(s)) => s.trim().parse::<f64>().map_err(|_| {
+                BuiltinError::InvalidValue(format!("cannot convert {} to `Float`", args[0].repr()))
+            })?,
+            Value::Data(DataType::Bool(b)) => f64::from(u8::from(*b)),
+            _ => return Err(rejected(&args, 0, Type::Scalar)),
+        };
+        Ok(Value::Data(DataType::Float(float)))
+    }
+}
@@ -0,0 +1,353 @@
+//! functions on strings.
+//! `len` and `contains` also take arrays, they are in the `array` module.
+//! indices and lengths count characters, not bytes, so they are safe for any UTF-8 text
+
+use crate::data::{ArithmeticError, DataType, MAX_LENGTH};
+
+use super::{array, int, string, BuiltinError, Function, Host, Parameter, Type, Value};
+
+pub(super) fn functions() -> Vec<&'static dyn Function> {
+    vec![
+        &Upper {},
+        &Lower {},
+        &Trim {},
+        &Split {},
+        &Join {},
+        &Replace {},
+        &STARTS_WITH,
+        &ENDS_WITH,
+        &Find {},
+        &Substring {},
+        &Chars {},
+        &ParseInt {},
+        &ParseFloat {},
+    ]
+}
+
+/// the parameters of the functions of a single string
+const STRING: &[Parameter] = &[Parameter::required("s", Type::String)];
+
+fn string_value(s: impl Into<String>) -> Value {
+    Value::Data(DataType::String(s.into()))
+}
+
+/// `length` is the length of a result, `None` if computing it overflowed
+fn check_length(length: Option<usize>) -> Result<(), BuiltinError> {
+    match length {
+        Some(length) if length <= MAX_LENGTH => Ok(()),
+        _ => Err(ArithmeticError::TooLarge.into()),
+    }
+}
+
+struct Upper {}
+impl Function for Upper {
+    fn name(&self) -> &'static str {
+        "upper"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        STRING
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(string_value(string(&args, 0)?.to_uppercase()))
+    }
+}
+
+struct Lower {}
+impl Function for Lower {
+    fn name(&self) -> &'static str {
+        "lower"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        STRING
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(string_value(string(&args, 0)?.to_lowercase()))
+    }
+}
+
+/// removes whitespace from both ends
+struct Trim {}
+impl Function for Trim {
+    fn name(&self) -> &'static str {
+        "trim"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        STRING
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        Ok(string_value(string(&args, 0)?.trim()))
+    }
+}
+
+/// `split(s, separator)`, without a separator `s` is split at runs of whitespace
+struct Split {}
+impl Function for Split {
+    fn name(&self) -> &'static str {
+        "split"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::optional("separator", Type::String),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let s = string(&args, 0)?;
+        let parts: Vec<Value> = match args.get(1) {
+            None => s.split_whitespace().map(string_value).collect(),
+            Some(_) => {
+                let separator = string(&args, 1)?;
+                if separator.is_empty() {
+                    return Err(BuiltinError::InvalidValue(
+                        "the separator cannot be empty, use `chars` to split into characters"
+                            .to_string(),
+                    ));
+                }
+                s.split(separator).map(string_value).collect()
+            }
+        };
+        Ok(Value::Array(parts))
+    }
+}
+
+/// `join(strings, separator)`, the separator defaults to an empty string
+struct Join {}
+impl Function for Join {
+    fn name(&self) -> &'static str {
+        "join"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("strings", Type::Strings),
+            Parameter::default("separator", Type::String, || Value::Data(DataType::String(String::new()))),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let separator = string(&args, 1)?;
+        // `validate` checked that every element is a string, which displays as it is
+        let parts: Vec<String> = array(&args, 0)?.iter().map(Value::to_string).collect();
+        let separators = separator.len().checked_mul(parts.len().saturating_sub(1));
+        check_length(separators.and_then(|length| {
+            parts.iter().try_fold(length, |length, part| length.checked_add(part.len()))
+        }))?;
+        Ok(string_value(parts.join(separator)))
+    }
+}
+
+/// `replace(s, from, to)` replaces every occurrence of `from`
+struct Replace {}
+impl Function for Replace {
+    fn name(&self) -> &'static str {
+        "replace"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::required("from", Type::String),
+            Parameter::required("to", Type::String),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let from = string(&args, 1)?;
+        if from.is_empty() {
+            return Err(BuiltinError::InvalidValue(
+                "the replaced string cannot be empty".to_string(),
+            ));
+        }
+        let (s, to) = (string(&args, 0)?, string(&args, 2)?);
+        // the occurrences do not overlap, so they are at most as long as `s`
+        let count = s.matches(from).count();
+        let kept = s.len() - count * from.len();
+        check_length(count.checked_mul(to.len()).and_then(|n| n.checked_add(kept)))?;
+        Ok(string_value(s.replace(from, to)))
+    }
+}
+
+/// a function testing two strings
+struct Test {
+    name: &'static str,
+    f: fn(&str, &str) -> bool,
+}
+impl Function for Test {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::required("pattern", Type::String),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let result = (self.f)(string(&args, 0)?, string(&args, 1)?);
+        Ok(Value::Data(DataType::Bool(result)))
+    }
+}
+
+const STARTS_WITH: Test = Test {
+    name: "starts_with",
+    f: |s, prefix| s.starts_with(prefix),
+};
+const ENDS_WITH: Test = Test {
+    name: "ends_with",
+    f: |s, suffix| s.ends_with(suffix),
+};
+
+/// the character index of the first occurrence, `-1` if there is none
+struct Find {}
+impl Function for Find {
+    fn name(&self) -> &'static str {
+        "find"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::required("pattern", Type::String),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let s = string(&args, 0)?;
+        let index = match s.find(string(&args, 1)?) {
+            Some(byte) => s[..byte].chars().count() as i64,
+            None => -1,
+        };
+        Ok(Value::Data(DataType::Int(index)))
+    }
+}
+
+/// `substring(s, start, end)`, the characters from `start` up to but excluding `end`,
+/// which defaults to the length of `s`
+struct Substring {}
+impl Function for Substring {
+    fn name(&self) -> &'static str {
+        "substring"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::required("start", Type::Int),
+            Parameter::optional("end", Type::Int),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let s = string(&args, 0)?;
+        let len = s.chars().count() as i64;
+        let start = int(&args, 1)?;
+        let end = match args.get(2) {
+            Some(_) => int(&args, 2)?,
+            None => len,
+        };
+        if !(0 <= start && start <= end && end <= len) {
+            return Err(BuiltinError::InvalidValue(format!(
+                "range {}..{} is out of bounds for a string of length {}",
+                start, end, len
+            )));
+        }
+        let substring: String = s
+            .chars()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect();
+        Ok(string_value(substring))
+    }
+}
+
+/// an array of single character strings
+struct Chars {}
+impl Function for Chars {
+    fn name(&self) -> &'static str {
+        "chars"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        STRING
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let chars = string(&args, 0)?
+            .chars()
+            .map(|c| string_value(c.to_string()))
+            .collect();
+        Ok(Value::Array(chars))
+    }
+}
+
+/// `parse_int(s, radix)`, the radix defaults to 10, unlike `int` fractions are an error
+struct ParseInt {}
+impl Function for ParseInt {
+    fn name(&self) -> &'static str {
+        "parse_int"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: &[Parameter] = &[
+            Parameter::required("s", Type::String),
+            Parameter::default("radix", Type::Int, || Value::Data(DataType::Int(10))),
+        ];
+        PARAMETERS
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        let s = string(&args, 0)?;
+        let radix = match args.get(1) {
+            Some(_) => int(&args, 1)?,
+            None => 10,
+        };
+        if !(2..=36).contains(&radix) {
+            return Err(BuiltinError::InvalidValue(format!(
+                "the radix must be between 2 and 36, found {}",
+                radix
+            )));
+        }
+        i64::from_str_radix(s.trim(), radix as u32)
+            .map(|n| Value::Data(DataType::Int(n)))
+            .map_err(|e| {
+                BuiltinError::InvalidValue(format!("cannot parse {} as `Int`: {}", args[0].repr(), e))
+            })
+    }
+}
+
+struct ParseFloat {}
+impl Function for ParseFloat {
+    fn name(&self) -> &'static str {
+        "parse_float"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        STRING
+    }
+
+    fn call(&self, args: Vec<Value>, _: &Host) -> Result<Value, BuiltinError> {
+        string(&args, 0)?
+            .trim()
+            .parse::<f64>()
+            .map(|n| Value::Data(DataType::Float(n)))
+            .map_err(|e| {
+                BuiltinError::InvalidValue(format!("cannot parse {} as `Float`: {}", args[0].repr(), e))
+            })
+    }
+}
@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// what a script may access outside of the interpreter.
+/// the default only allows writing to stdout, so untrusted scripts can be run with
+/// `Capabilities::default()` or `Capabilities::none()`.
+///
+/// paths are checked before the file is opened. another process that can write to a granted
+/// directory may replace a checked file with a symbolic link in between, so only grant
+/// directories that untrusted processes cannot modify
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    /// `print`
+    pub stdout: bool,
+    /// `read_line`
+    pub stdin: bool,
+    /// `read_file`, allowed for files inside these directories, or these files themselves
+    pub fs_read: Vec<PathBuf>,
+    /// `write_file`, allowed like `fs_read`
+    pub fs_write: Vec<PathBuf>,
+    /// `env`
+    pub env: bool,
+    /// `now`
+    pub clock: bool,
+}
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            stdout: true,
+            ..Self::none()
+        }
+    }
+}
+impl Capabilities {
+    /// denies everything, scripts can only return values
+    pub fn none() -> Self {
+        Self {
+            stdout: false,
+            stdin: false,
+            fs_read: Vec::new(),
+            fs_write: Vec::new(),
+            env: false,
+            clock: false,
+        }
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Stdout => self.stdout,
+            Capability::Stdin => self.stdin,
+            Capability::FsRead => !self.fs_read.is_empty(),
+            Capability::FsWrite => !self.fs_write.is_empty(),
+            Capability::Env => self.env,
+            Capability::Clock => self.clock,
+        }
+    }
+
+    /// whether `path` may be read, `..` and symbolic links are resolved before checking
+    pub fn may_read(&self, path: &Path) -> bool {
+        within(&self.fs_read, path)
+    }
+
+    /// whether `path` may be written, it does not need to exist yet
+    pub fn may_write(&self, path: &Path) -> bool {
+        within(&self.fs_write, path)
+    }
+}
+
+/// the absolute path without `..` or symbolic links, the file itself does not need to exist.
+/// `None` for a symbolic link whose target does not exist, writing to it would create the target
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if let Ok(path) = path.canonicalize() {
+        return Some(path);
+    }
+    if path.symlink_metadata().is_ok() {
+        return None;
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path.file_name()?;
+    Some(parent.canonicalize().ok()?.join(name))
+}
+
+fn within(allowed: &[PathBuf], path: &Path) -> bool {
+    let Some(path) = resolve(path) else {
+        return false;
+    };
+    allowed
+        .iter()
+        .filter_map(|allowed| resolve(allowed))
+        .any(|allowed| path.starts_with(allowed))
+}
+
+/// a group of built-in functions that access the host
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Capability {
+    Stdout,
+    Stdin,
+    FsRead,
+    FsWrite,
+    Env,
+    Clock,
+}
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Stdout => "stdout",
+            Self::Stdin => "stdin",
+            Self::FsRead => "fs_read",
+            Self::FsWrite => "fs_write",
+            Self::Env => "env",
+            Self::Clock => "clock",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// what built-in functions can access outside of the interpreter, shared by every call
+pub struct Host {
+    pub capabilities: Capabilities,
+    /// where `print` writes, stdout unless the executor replaces it
+    pub output: RefCell<Box<dyn Write>>,
+}
+impl Host {
+    pub fn new(capabilities: Capabilities) -> Self {
+        Self {
+            capabilities,
+            output: RefCell::new(Box::new(io::stdout())),
+        }
+    }
+}
+impl fmt::Debug for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Host")
+            .field("capabilities", &self.capabilities)
+            .finish_non_exhaustive()
+    }
+}
+
+/// collects the output of a script, clones share the same buffer
+#[derive(Clone, Debug, Default)]
+pub struct Capture(Rc<RefCell<Vec<u8>>>);
+impl Capture {
+    /// everything written so far, invalid UTF-8 is replaced
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+
+    /// empties the buffer, returning what it held
+    pub fn take(&self) -> String {
+        let contents = self.contents();
+        self.0.borrow_mut().clear();
+        contents
+    }
+}
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
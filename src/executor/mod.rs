@@ -1,11 +1,20 @@
+mod check;
 mod context;
 mod functions;
+mod host;
 pub mod value;
 
 use crate::parser::Ast;
 pub use crate::data::IntoValue;
+pub use check::check;
+pub use context::MAX_CALL_DEPTH;
+pub use host::{Capabilities, Capability, Capture};
+
+use std::io::Write;
+use std::rc::Rc;
 
 use context::Context;
+use host::Host;
 use crate::error::ExecutionError;
 use crate::data::Value;
 
@@ -13,19 +22,62 @@ use crate::data::Value;
 pub struct Executor {
     pub ast: Ast,
     pub context: Context,
+    host: Rc<Host>,
 }
 impl Executor {
+    /// with `Capabilities::default()`, scripts may print but not access anything else
     pub fn build(ast: Ast) -> Result<Self, ExecutionError> {
-        let context = Context::build(&ast)?;
-        Ok(Self { ast, context })
+        Self::build_with(ast, Capabilities::default())
+    }
+
+    pub fn build_with(ast: Ast, capabilities: Capabilities) -> Result<Self, ExecutionError> {
+        let host = Rc::new(Host::new(capabilities));
+        let context = Context::build(&ast, host.clone())?;
+        Ok(Self { ast, context, host })
+    }
+
+    /// sends the output of `print` to `output` instead of stdout,
+    /// this executor and its clones share the writer
+    pub fn set_output(&self, output: impl Write + 'static) {
+        *self.host.output.borrow_mut() = Box::new(output);
+    }
+
+    /// collects the output of `print` into the returned buffer instead of writing it to stdout
+    pub fn capture_output(&self) -> Capture {
+        let capture = Capture::default();
+        self.set_output(capture.clone());
+        capture
+    }
+
+    /// runs `ast` as if it was appended to the script, its functions and variables are kept
+    pub fn eval(&mut self, ast: Ast) -> Result<Value, ExecutionError> {
+        let value = self.context.extend(&ast);
+        self.ast.nodes.extend(ast.nodes);
+        value
+    }
+
+    /// a variable the script declared outside of functions, or that `set_global` set
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.context.global(name)
+    }
+
+    /// declares or assigns a global, an error if the script declared it with `const`.
+    /// like assignments by functions, it is seen by every later call and by clones of this executor
+    pub fn set_global(&self, name: &str, value: &dyn IntoValue) -> Result<(), ExecutionError> {
+        self.context.set_global(name, value.into_value(), false, None)
+    }
+
+    /// every global sorted by name, including built-in constants like `math::PI`
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        self.context.globals()
     }
 
     pub fn execute(
         &self,
         entry: &str,
         arguments: Vec<&dyn IntoValue>,
-    ) -> Result<Option<Value>, ExecutionError> {
+    ) -> Result<Value, ExecutionError> {
         let values = arguments.into_iter().map(|v| v.into_value()).collect();
-        self.context.call_function(entry, values)
+        self.context.call_function(entry, values, Vec::new(), None)
     }
 }
@@ -4,17 +4,23 @@ pub use tokenstream::TokenStream;
 pub mod kind;
 // pub use kind::TokenKind;
 
+mod string;
+
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
-    character::complete::{alphanumeric1, char, digit1},
-    combinator::{map, opt, recognize},
-    multi::{many0, many1},
-    sequence::{pair, preceded, terminated},
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, char, digit1, satisfy},
+    combinator::{map, not, opt, recognize},
+    error::ErrorKind,
+    multi::many1,
+    sequence::{pair, terminated},
     IResult,
 };
 
 use crate::data::DataType;
+use crate::diagnostic::ErrorCode;
+use crate::error::ParseError;
+use crate::span::{FileId, Span};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenKind {
@@ -38,6 +44,10 @@ pub enum TokenKind {
     Equal,
     Greater,
     Less,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
 
     // One or two character tokens
     BangEqual,
@@ -45,10 +55,21 @@ pub enum TokenKind {
     GreaterEqual,
     LessEqual,
     Arrow,
+    StarStar,
+    LessLess,
+    GreaterGreater,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    AmpersandAmpersand,
+    PipePipe,
 
     // Literals
     Identifier,
     StringLiteral,
+    InterpolatedString,
     FloatLiteral,
     IntLiteral,
 
@@ -60,6 +81,7 @@ pub enum TokenKind {
     Fn,
     Return,
     Let,
+    Const,
     While,
 
     // End of file
@@ -67,38 +89,235 @@ pub enum TokenKind {
     FloatLIteral,
 }
 
+impl TokenKind {
+    /// tokens that can start an expression
+    pub const EXPRESSION_START: [TokenKind; 11] = [
+        TokenKind::Identifier,
+        TokenKind::FloatLiteral,
+        TokenKind::StringLiteral,
+        TokenKind::InterpolatedString,
+        TokenKind::True,
+        TokenKind::False,
+        TokenKind::If,
+        TokenKind::LeftParen,
+        TokenKind::LeftBracket,
+        TokenKind::LeftBrace,
+        TokenKind::Bang,
+    ];
+}
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::LeftParen => "(",
+            Self::RightParen => ")",
+            Self::LeftBrace => "{",
+            Self::RightBrace => "}",
+            Self::LeftBracket => "[",
+            Self::RightBracket => "]",
+            Self::Comma => ",",
+            Self::Dot => ".",
+            Self::Minus => "-",
+            Self::Plus => "+",
+            Self::Semicolon | Self::SemiColon => ";",
+            Self::Slash => "/",
+            Self::Star => "*",
+            Self::Colon => ":",
+            Self::Bang => "!",
+            Self::Equal => "=",
+            Self::Greater => ">",
+            Self::Less => "<",
+            Self::Percent => "%",
+            Self::Ampersand => "&",
+            Self::Pipe => "|",
+            Self::Caret => "^",
+            Self::BangEqual => "!=",
+            Self::EqualEqual => "==",
+            Self::GreaterEqual => ">=",
+            Self::LessEqual => "<=",
+            Self::Arrow => "->",
+            Self::StarStar => "**",
+            Self::LessLess => "<<",
+            Self::GreaterGreater => ">>",
+            Self::PlusEqual => "+=",
+            Self::MinusEqual => "-=",
+            Self::StarEqual => "*=",
+            Self::SlashEqual => "/=",
+            Self::PercentEqual => "%=",
+            Self::AmpersandAmpersand => "&&",
+            Self::PipePipe => "||",
+            Self::If => "if",
+            Self::Else => "else",
+            Self::True => "true",
+            Self::False => "false",
+            Self::Fn => "fn",
+            Self::Return => "return",
+            Self::Let => "let",
+            Self::Const => "const",
+            Self::While => "while",
+            Self::Identifier => return write!(f, "identifier"),
+            Self::StringLiteral | Self::InterpolatedString => return write!(f, "string"),
+            Self::FloatLiteral | Self::FloatLIteral | Self::IntLiteral => {
+                return write!(f, "number")
+            }
+            Self::EOF => return write!(f, "end of input"),
+        };
+        write!(f, "`{}`", symbol)
+    }
+}
+
+/// part of an interpolated string, e.g. `"value: {x + 1}"`
+#[derive(Clone, Debug, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    Expression(Vec<Token>),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
     pub data: Option<DataType>,
-    pub index: usize,
+    pub span: Span,
+    /// only used by `TokenKind::InterpolatedString`
+    pub parts: Vec<StringPart>,
 }
 impl Token {
     pub fn empty(kind: TokenKind) -> Self {
-        let token = Self { kind, data: None, index: 0 };
-        token
+        Self::new(kind, None)
     }
     pub fn new(kind: TokenKind, data: Option<DataType>) -> Self {
-        Self { kind, data, index: 0 }
+        Self {
+            kind,
+            data,
+            span: Span::default(),
+            parts: Vec::new(),
+        }
+    }
+    pub fn interpolated(parts: Vec<StringPart>) -> Self {
+        Self {
+            parts,
+            ..Self::empty(TokenKind::InterpolatedString)
+        }
+    }
+
+    /// moves the token and the tokens of embedded expressions by `offset`
+    pub fn offset(&mut self, offset: usize) {
+        self.span = self.span.offset(offset);
+        for part in self.parts.iter_mut() {
+            if let StringPart::Expression(tokens) = part {
+                for token in tokens.iter_mut() {
+                    token.offset(offset);
+                }
+            }
+        }
+    }
+
+    /// assigns the token and the tokens of embedded expressions to `file`
+    pub fn set_file(&mut self, file: FileId) {
+        self.span = self.span.with_file(file);
+        for part in self.parts.iter_mut() {
+            if let StringPart::Expression(tokens) = part {
+                for token in tokens.iter_mut() {
+                    token.set_file(file);
+                }
+            }
+        }
+    }
+}
+
+/// error of a single lexer function, `input` is the remaining input at the point of failure
+/// and `len` the length in bytes of the offending text
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError<'a> {
+    pub input: &'a str,
+    pub len: usize,
+    pub cause: Option<String>,
+}
+impl<'a> LexError<'a> {
+    pub fn new(input: &'a str, len: usize, cause: String) -> Self {
+        Self {
+            input,
+            len,
+            cause: Some(cause),
+        }
     }
 }
+impl<'a> nom::error::ParseError<&'a str> for LexError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            len: 0,
+            cause: None,
+        }
+    }
 
-pub fn tokenize(input: &str) -> TokenStream {
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+type LexResult<'a, O> = IResult<&'a str, O, LexError<'a>>;
+
+/// like `tokenize`, but every span refers to `file` of a `SourceMap`
+pub fn tokenize_file(input: &str, file: FileId) -> Result<TokenStream, ParseError> {
+    match tokenize(input) {
+        Ok(tokens) => Ok(tokens
+            .map(|mut token| {
+                token.set_file(file);
+                token
+            })
+            .collect()),
+        Err(mut e) => {
+            e.span = e.span.with_file(file);
+            Err(e)
+        }
+    }
+}
+
+pub fn tokenize(input: &str) -> Result<TokenStream, ParseError> {
+    match lex(input) {
+        Ok(tokens) => Ok(TokenStream::from(tokens)),
+        Err(e) => {
+            let start = input.len() - e.input.len();
+            Err(ParseError::new(
+                Span::new(start, start + e.len),
+                e.cause.unwrap_or_else(|| "invalid token".to_string()),
+            )
+            .with_code(ErrorCode::InvalidToken))
+        }
+    }
+}
+
+/// token spans are relative to the start of `input`,
+/// the input of the returned error is always a suffix of `input`
+fn lex(input: &str) -> Result<Vec<Token>, LexError<'_>> {
     let mut tokens = Vec::new();
 
     let initial_length = input.len(); // Store the initial length of the input
-    let mut input = input.trim();
+    // only trim the start, so the indices stay relative to the original input
+    let mut input = input.trim_start();
     // let mut index = initial_length - input.len();
 
     while !input.is_empty() {
-        let (remaining_input, mut token) = match token(input) {
-            Ok((rest, token)) => (rest.trim_start(), token), // Trim only the start of the input
-            Err(_) => break,
+        let (rest, mut token) = match token(input) {
+            Ok(t) => t,
+            // hard failures, e.g. invalid escape sequences, are reported
+            Err(nom::Err::Failure(e)) => return Err(e),
+            Err(_) => {
+                let c = input.chars().next().unwrap_or_default();
+                return Err(LexError::new(
+                    input,
+                    c.len_utf8(),
+                    format!("invalid token '{}'", c.escape_debug()),
+                ));
+            }
         };
 
-        // let index = initial_length - remaining_input.len();
-        let index = initial_length - input.len();
-        token.index = index;
+        // token spans are relative to the start of the token until here
+        let start = initial_length - input.len();
+        token.span = Span::new(0, input.len() - rest.len());
+        token.offset(start);
+
+        let remaining_input = rest.trim_start(); // Trim only the start of the input
 
         tokens.push(token);
         // index += input.len() - remaining_input.len();
@@ -106,13 +325,13 @@ pub fn tokenize(input: &str) -> TokenStream {
         input = remaining_input;
     }
 
-    TokenStream::from(tokens)
+    Ok(tokens)
 }
 
-fn token(input: &str) -> IResult<&str, Token> {
+fn token(input: &str) -> LexResult<'_, Token> {
     // order matters
     alt((
-        string_literal,
+        string::string_literal,
         float_literal,
         int_literal,
         identifier_or_keyword,
@@ -121,7 +340,12 @@ fn token(input: &str) -> IResult<&str, Token> {
     ))(input)
 }
 
-fn single_char_token(input: &str) -> IResult<&str, Token> {
+fn single_char_token(input: &str) -> LexResult<'_, Token> {
+    // nom supports at most 21 alternatives per `alt`
+    alt((single_char_punctuation, single_char_operator))(input)
+}
+
+fn single_char_punctuation(input: &str) -> LexResult<'_, Token> {
     alt((
         map(char('('), |_| Token::empty(TokenKind::LeftParen)),
         map(char(')'), |_| Token::empty(TokenKind::RightParen)),
@@ -138,33 +362,43 @@ fn single_char_token(input: &str) -> IResult<&str, Token> {
         map(char('*'), |_| Token::empty(TokenKind::Star)),
         map(char(':'), |_| Token::empty(TokenKind::Colon)),
         map(char(';'), |_| Token::empty(TokenKind::SemiColon)),
+    ))(input)
+}
+
+fn single_char_operator(input: &str) -> LexResult<'_, Token> {
+    alt((
         map(char('!'), |_| Token::empty(TokenKind::Bang)),
         map(char('='), |_| Token::empty(TokenKind::Equal)),
         map(char('>'), |_| Token::empty(TokenKind::Greater)),
         map(char('<'), |_| Token::empty(TokenKind::Less)),
+        map(char('%'), |_| Token::empty(TokenKind::Percent)),
+        map(char('&'), |_| Token::empty(TokenKind::Ampersand)),
+        map(char('|'), |_| Token::empty(TokenKind::Pipe)),
+        map(char('^'), |_| Token::empty(TokenKind::Caret)),
     ))(input)
 }
 
-fn multiple_char_token(input: &str) -> IResult<&str, Token> {
+fn multiple_char_token(input: &str) -> LexResult<'_, Token> {
     alt((
         map(tag("!="), |_| Token::empty(TokenKind::BangEqual)),
         map(tag("=="), |_| Token::empty(TokenKind::EqualEqual)),
         map(tag(">="), |_| Token::empty(TokenKind::GreaterEqual)),
         map(tag("<="), |_| Token::empty(TokenKind::LessEqual)),
         map(tag("->"), |_| Token::empty(TokenKind::Arrow)),
+        map(tag("**"), |_| Token::empty(TokenKind::StarStar)),
+        map(tag("<<"), |_| Token::empty(TokenKind::LessLess)),
+        map(tag(">>"), |_| Token::empty(TokenKind::GreaterGreater)),
+        map(tag("+="), |_| Token::empty(TokenKind::PlusEqual)),
+        map(tag("-="), |_| Token::empty(TokenKind::MinusEqual)),
+        map(tag("*="), |_| Token::empty(TokenKind::StarEqual)),
+        map(tag("/="), |_| Token::empty(TokenKind::SlashEqual)),
+        map(tag("%="), |_| Token::empty(TokenKind::PercentEqual)),
+        map(tag("&&"), |_| Token::empty(TokenKind::AmpersandAmpersand)),
+        map(tag("||"), |_| Token::empty(TokenKind::PipePipe)),
     ))(input)
 }
 
-fn string_literal(input: &str) -> IResult<&str, Token> {
-    let string_content = recognize(many0(alt((take_while1(|c| c != '"'), tag("\\\"")))));
-    let string_parser = preceded(char('"'), terminated(string_content, char('"')));
-    map(string_parser, |s: &str| {
-        let data = Some(DataType::String(String::from(s)));
-        Token::new(TokenKind::StringLiteral, data)
-    })(input)
-}
-
-fn float_literal(input: &str) -> IResult<&str, Token> {
+fn float_literal(input: &str) -> LexResult<'_, Token> {
     // let (input, num_str) = recognize(
     //     pair(digit1, pair(char('.'), digit1))
     // )(input)?;
@@ -177,32 +411,39 @@ fn float_literal(input: &str) -> IResult<&str, Token> {
     // let num = num_str.parse::<f64>().unwrap();
     let data = num_str
         .parse::<f64>()
-        .ok()
-        .and_then(|d| Some(DataType::Float(d)));
+        .ok().map(DataType::Float);
     Ok((input, Token::new(TokenKind::FloatLiteral, data)))
 }
 
-fn int_literal(input: &str) -> IResult<&str, Token> {
+fn int_literal(input: &str) -> LexResult<'_, Token> {
     let (input, num_str) = recognize(
         pair(opt(tag("-")), digit1)
     )(input)?;
     let data = num_str
         .parse::<i64>()
-        .ok()
-        .and_then(|d| Some(DataType::Int(d)));
+        .ok().map(DataType::Int);
     Ok((input, Token::new(TokenKind::FloatLiteral, data)))
 }
 
-fn identifier_or_keyword(input: &str) -> IResult<&str, Token> {
+/// `word` unless it starts a longer identifier, e.g. `let` in `letter`
+fn keyword<'a>(word: &'static str, kind: TokenKind) -> impl FnMut(&'a str) -> LexResult<'a, Token> {
+    map(
+        terminated(tag(word), not(satisfy(|c: char| c.is_alphanumeric() || c == '_'))),
+        move |_| Token::empty(kind.clone()),
+    )
+}
+
+fn identifier_or_keyword(input: &str) -> LexResult<'_, Token> {
     let (remaining_input, token) = alt((
-        map(tag("if"), |_| Token::empty(TokenKind::If)),
-        map(tag("else"), |_| Token::empty(TokenKind::Else)),
-        map(tag("true"), |_| Token::empty(TokenKind::True)),
-        map(tag("false"), |_| Token::empty(TokenKind::False)),
-        map(tag("fn"), |_| Token::empty(TokenKind::Fn)),
-        map(tag("return"), |_| Token::empty(TokenKind::Return)),
-        map(tag("let"), |_| Token::empty(TokenKind::Let)),
-        map(tag("while"), |_| Token::empty(TokenKind::While)),
+        keyword("if", TokenKind::If),
+        keyword("else", TokenKind::Else),
+        keyword("true", TokenKind::True),
+        keyword("false", TokenKind::False),
+        keyword("fn", TokenKind::Fn),
+        keyword("return", TokenKind::Return),
+        keyword("let", TokenKind::Let),
+        keyword("const", TokenKind::Const),
+        keyword("while", TokenKind::While),
         // map(alphanumeric1, |i: &str| Token::Identifier(i.to_string()))
         map(
             // pair(alphanumeric1, tag("_")),
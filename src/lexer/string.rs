@@ -0,0 +1,232 @@
+use nom::{
+    branch::alt,
+    character::complete::char,
+    multi::many0_count,
+    sequence::preceded,
+};
+
+use super::{lex, LexError, LexResult, StringPart, Token, TokenKind};
+use crate::data::DataType;
+
+/// parses either a raw string (`r"..."`, `r#"..."#`) or a normal string with escape sequences
+pub fn string_literal(input: &str) -> LexResult<'_, Token> {
+    alt((raw_string, escaped_string))(input)
+}
+
+/// raw strings take their content verbatim, the amount of `#` decides how the string is terminated
+fn raw_string(input: &str) -> LexResult<'_, Token> {
+    let (rest, hashes) = preceded(char('r'), many0_count(char('#')))(input)?;
+    let (rest, _) = char('"')(rest)?;
+
+    let terminator = format!("\"{}", "#".repeat(hashes));
+    let Some(end) = rest.find(&terminator) else {
+        return Err(nom::Err::Failure(LexError::new(
+            input,
+            input.len() - rest.len(),
+            "unterminated raw string literal".to_string(),
+        )));
+    };
+    let data = Some(DataType::String(rest[..end].to_string()));
+    Ok((
+        &rest[end + terminator.len()..],
+        Token::new(TokenKind::StringLiteral, data),
+    ))
+}
+
+/// `{expr}` embeds an expression, `{{` and `}}` produce literal braces
+fn escaped_string(input: &str) -> LexResult<'_, Token> {
+    let (mut rest, _) = char('"')(input)?;
+    let mut string = String::new();
+    let mut parts = Vec::new();
+
+    loop {
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('"') => {
+                rest = chars.as_str();
+                break;
+            }
+            Some('\\') => {
+                let (remaining, c) = escape(rest)?;
+                if let Some(c) = c {
+                    string.push(c);
+                }
+                rest = remaining;
+            }
+            Some('{') if chars.as_str().starts_with('{') => {
+                string.push('{');
+                rest = &rest[2..];
+            }
+            Some('}') if chars.as_str().starts_with('}') => {
+                string.push('}');
+                rest = &rest[2..];
+            }
+            Some('{') => {
+                let (remaining, tokens) = interpolation(input, rest)?;
+                if !string.is_empty() {
+                    parts.push(StringPart::Literal(std::mem::take(&mut string)));
+                }
+                parts.push(StringPart::Expression(tokens));
+                rest = remaining;
+            }
+            Some('}') => {
+                return Err(nom::Err::Failure(LexError::new(
+                    rest,
+                    1,
+                    "unmatched '}' in string, use '}}' for a literal brace".to_string(),
+                )));
+            }
+            Some(c) => {
+                string.push(c);
+                rest = chars.as_str();
+            }
+            None => {
+                return Err(nom::Err::Failure(LexError::new(
+                    input,
+                    1,
+                    "unterminated string literal".to_string(),
+                )));
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        let data = Some(DataType::String(string));
+        return Ok((rest, Token::new(TokenKind::StringLiteral, data)));
+    }
+    if !string.is_empty() {
+        parts.push(StringPart::Literal(string));
+    }
+    Ok((rest, Token::interpolated(parts)))
+}
+
+/// `input` starts at the opening quote of the string, `rest` at the opening '{' of the expression,
+/// the returned tokens are positioned relative to the opening quote
+fn interpolation<'a>(
+    input: &'a str,
+    rest: &'a str,
+) -> Result<(&'a str, Vec<Token>), nom::Err<LexError<'a>>> {
+    let expression = &rest[1..];
+    let Some(end) = expression_end(expression) else {
+        return Err(nom::Err::Failure(LexError::new(
+            rest,
+            1,
+            "unterminated interpolation, missing closing '}'".to_string(),
+        )));
+    };
+    let source = &expression[..end];
+    if source.trim().is_empty() {
+        return Err(nom::Err::Failure(LexError::new(
+            rest,
+            end + 2,
+            "empty expression in string interpolation".to_string(),
+        )));
+    }
+
+    let mut tokens = lex(source).map_err(|e| {
+        // map the error back onto the remaining input of the whole string
+        let at = source.len() - e.input.len();
+        nom::Err::Failure(LexError {
+            input: &expression[at..],
+            len: e.len,
+            cause: e.cause,
+        })
+    })?;
+    let offset = input.len() - expression.len();
+    for token in tokens.iter_mut() {
+        token.offset(offset);
+    }
+
+    Ok((&expression[end + 1..], tokens))
+}
+
+/// finds the '}' closing an embedded expression, skipping nested braces and strings
+fn expression_end(expression: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in expression.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// expects the input to start at the backslash,
+/// returns `None` for escapes that do not produce a character (line continuation)
+fn escape(input: &str) -> Result<(&str, Option<char>), nom::Err<LexError<'_>>> {
+    let mut chars = input.chars();
+    chars.next();
+    let c = match chars.next() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('\'') => '\'',
+        Some('u') => return unicode_escape(input, chars.as_str()),
+        // line continuation, skips the newline and the indentation of the next line
+        Some('\n') => return Ok((chars.as_str().trim_start(), None)),
+        Some(c) => {
+            return Err(nom::Err::Failure(LexError::new(
+                input,
+                1 + c.len_utf8(),
+                format!("invalid escape sequence: '\\{}'", c.escape_debug()),
+            )))
+        }
+        None => {
+            return Err(nom::Err::Failure(LexError::new(
+                input,
+                1,
+                "unterminated string literal".to_string(),
+            )))
+        }
+    };
+    Ok((chars.as_str(), Some(c)))
+}
+
+/// handles `\u{...}` with 1 to 6 hex digits, `rest` starts right after the `u`
+fn unicode_escape<'a>(
+    input: &'a str,
+    rest: &'a str,
+) -> Result<(&'a str, Option<char>), nom::Err<LexError<'a>>> {
+    let invalid =
+        |len: usize, cause: &str| nom::Err::Failure(LexError::new(input, len, cause.to_string()));
+
+    let rest = rest
+        .strip_prefix('{')
+        .ok_or_else(|| invalid(2, "invalid unicode escape, expected '{' after '\\u'"))?;
+    let end = rest
+        .find('}')
+        .ok_or_else(|| invalid(3, "invalid unicode escape, missing closing '}'"))?;
+    // covers the whole `\u{...}`
+    let len = input.len() - rest.len() + end + 1;
+    let digits = &rest[..end];
+    if digits.is_empty() || digits.len() > 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid(
+            len,
+            "invalid unicode escape, expected 1 to 6 hexadecimal digits",
+        ));
+    }
+    let c = u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| invalid(len, "invalid unicode escape, not a valid unicode scalar value"))?;
+
+    Ok((&rest[end + 1..], Some(c)))
+}
@@ -1,23 +1,35 @@
+use std::cell::RefCell;
 use std::ops::Index;
+use std::rc::Rc;
 use super::{Token, TokenKind};
+use crate::data::DataType;
+use crate::error::ParseError;
+use crate::span::Span;
 // use nom::{InputLength, InputTake};
 
 #[derive(Clone, Debug)]
 pub struct TokenStream {
     token: Vec<Token>,
     pointer: usize,
+    /// furthest failure of the alternatives tried so far, shared with clones and inner streams
+    failure: Rc<RefCell<Option<ParseError>>>,
 }
 
 impl TokenStream {
     pub fn new(token: &[Token]) -> Self {
+        Self::from(Vec::from(token))
+    }
+
+    pub fn from(token: Vec<Token>) -> Self {
         Self {
-            token: Vec::from(token),
+            token,
             pointer: 0,
+            failure: Rc::default(),
         }
     }
 
-    pub fn from(token: Vec<Token>) -> Self {
-        Self { token, pointer: 0 }
+    pub fn len(&self) -> usize {
+        self.token.len()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -37,10 +49,88 @@ impl TokenStream {
         self.token.get(i)
     }
 
-    pub fn get_current_index(&self) -> Option<usize> {
-        Some(self.peek(0)?.index)
+    pub fn get_current_span(&self) -> Option<Span> {
+        Some(self.peek(0)?.span)
+    }
+
+    /// span of the last consumed token
+    pub fn previous_span(&self) -> Option<Span> {
+        let i = self.pointer.checked_sub(1)?;
+        Some(self.token.get(i)?.span)
+    }
+
+    /// empty span at the end of the last consumed token, used when the input ran out
+    fn end_span(&self) -> Span {
+        let end = self.previous_span().or(self.token.last().map(|t| t.span));
+        match end {
+            Some(span) => Span {
+                start: span.end,
+                ..span
+            },
+            None => Span::default(),
+        }
+    }
+
+    /// error at the current token, which is none of `expected`
+    pub fn unexpected(&self, expected: Vec<TokenKind>) -> ParseError {
+        match self.peek(0) {
+            Some(t) => ParseError::expected(t.span, expected, Some(t.kind.clone())),
+            None => ParseError::expected(self.end_span(), expected, None),
+        }
+    }
+
+    /// consumes the current token if it is of `kind`
+    pub fn expect(&mut self, kind: &TokenKind) -> Result<Token, ParseError> {
+        match self.peek(0) {
+            Some(t) if &t.kind == kind => {
+                let token = t.clone();
+                self.advance(1);
+                Ok(token)
+            }
+            _ => Err(self.unexpected(vec![kind.clone()])),
+        }
+    }
+
+    /// consumes the current token if it is an identifier and returns its name
+    pub fn expect_identifier(&mut self) -> Result<(String, Span), ParseError> {
+        let token = self.expect(&TokenKind::Identifier)?;
+        match token.data {
+            Some(DataType::String(name)) => Ok((name, token.span)),
+            _ => Err(ParseError::new(token.span, "invalid identifier".to_string())),
+        }
     }
 
+    /// remembers the error of a failed alternative
+    pub fn record_failure(&self, error: ParseError) {
+        let mut failure = self.failure.borrow_mut();
+        *failure = Some(match failure.take() {
+            Some(previous) => previous.merge(error),
+            None => error,
+        });
+    }
+
+    /// `error` merged with the furthest failure of the alternatives tried since the last reset
+    pub fn furthest_failure(&self, error: ParseError) -> ParseError {
+        match self.failure.borrow().clone() {
+            Some(failure) => failure.merge(error),
+            None => error,
+        }
+    }
+
+    /// forgets recorded failures, called at the start of every statement
+    pub fn reset_failures(&self) {
+        *self.failure.borrow_mut() = None;
+    }
+
+    /// span from `start` up to and including the last consumed token
+    pub fn span_from(&self, start: Span) -> Span {
+        match self.previous_span() {
+            Some(end) => start.to(end),
+            None => start,
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<&Token> {
         let tmp = self
             .token
@@ -57,6 +147,45 @@ impl TokenStream {
         self.pointer += advance;
     }
 
+    /// error recovery, skips to the start of the next statement:
+    /// past the next ';', past a block opened on the way, or right before the '}' closing the
+    /// enclosing block
+    pub fn synchronize(&mut self) {
+        let mut depth = 0;
+        while let Some(t) = self.peek(0) {
+            match t.kind {
+                TokenKind::Semicolon if depth == 0 => {
+                    self.advance(1);
+                    return;
+                }
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace if depth == 0 => return,
+                TokenKind::RightBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance(1);
+                        self.skip_if(&TokenKind::Semicolon);
+                        return;
+                    }
+                }
+                _ => (),
+            }
+            self.advance(1);
+        }
+    }
+
+    /// like `peek_pair_counting_stripped_inclusive`, but returns a stream over the tokens,
+    /// sharing the recorded failures with this stream
+    pub fn inner_stream(&mut self, left: &TokenKind, right: &TokenKind) -> Option<TokenStream> {
+        let failure = self.failure.clone();
+        let inner = self.peek_pair_counting_stripped_inclusive(left, right)?;
+        Some(Self {
+            token: Vec::from(inner),
+            pointer: 0,
+            failure,
+        })
+    }
+
     /// expects the first left token to be stripped
     /// does not ommit the last right token
     pub fn peek_pair_counting_stripped_inclusive(
@@ -99,7 +228,7 @@ impl Iterator for TokenStream {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.token.len() > 0 {
+        if !self.token.is_empty() {
             Some(self.token.remove(0))
         } else {
             None
@@ -3,3 +3,6 @@ pub mod parser;
 pub mod executor;
 pub mod data;
 pub mod error;
+pub mod span;
+pub mod source;
+pub mod diagnostic;
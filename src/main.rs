@@ -1,32 +1,181 @@
-use bs::executor::Executor;
-use bs::lexer::tokenize;
+mod repl;
+
+use bs::executor::{self, Executor, IntoValue};
+use bs::lexer::tokenize_file;
 use bs::parser::{self, Ast};
+use bs::source::SourceMap;
+use std::process;
 use std::time::Instant;
-use std::{fs, process};
+
+const USAGE: &str = "\
+usage: bs run <file> [--entry <fn>] [--time] [--no-color] [-- <args>...]
+       bs check <file> [--no-color]
+       bs repl [--no-color]
+
+commands:
+    run      runs the entry function, `main` by default, with <args> as `String` arguments
+    check    parses the file and checks it without running anything
+    repl     evaluates statements interactively, see `:help` inside
+
+exit codes:
+    0  success
+    1  the script failed while running
+    2  invalid command line or unreadable file
+    3  parse errors
+    4  the script could not be built";
+
+const EXIT_RUNTIME: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARSE: i32 = 3;
+const EXIT_BUILD: i32 = 4;
+
+enum Command {
+    Run,
+    Check,
+    Repl,
+}
+
+struct Options {
+    command: Command,
+    path: String,
+    entry: String,
+    arguments: Vec<String>,
+    color: bool,
+    time: bool,
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(EXIT_USAGE);
+}
+
+fn parse_options(args: impl Iterator<Item = String>) -> Options {
+    let mut args = args.peekable();
+    let command = match args.next().as_deref() {
+        Some("run") => Command::Run,
+        Some("check") => Command::Check,
+        Some("repl") => Command::Repl,
+        Some("-h" | "--help" | "help") => {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Some(other) => usage_error(&format!("unknown command '{}'", other)),
+        None => usage_error("missing command"),
+    };
+    let mut path = None;
+    let mut entry = None;
+    let mut arguments = Vec::new();
+    let mut color = true;
+    let mut time = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {
+                arguments.extend(args.by_ref());
+            }
+            "--no-color" => color = false,
+            "--time" => time = true,
+            "--entry" => match args.next() {
+                Some(name) => entry = Some(name),
+                None => usage_error("'--entry' needs a function name"),
+            },
+            flag if flag.starts_with('-') => usage_error(&format!("unknown option '{}'", flag)),
+            _ if path.is_none() => path = Some(arg),
+            _ => usage_error(&format!("unexpected argument '{}', script arguments follow '--'", arg)),
+        }
+    }
+    if matches!(command, Command::Repl) {
+        if path.is_some() || entry.is_some() || time || !arguments.is_empty() {
+            usage_error("'repl' only takes '--no-color'");
+        }
+        path = Some(String::new());
+    }
+    let Some(path) = path else {
+        usage_error("missing file");
+    };
+    if matches!(command, Command::Check) && (entry.is_some() || time || !arguments.is_empty()) {
+        usage_error("'check' only takes a file and '--no-color'");
+    }
+    Options {
+        command,
+        path,
+        entry: entry.unwrap_or_else(|| "main".to_string()),
+        arguments,
+        color,
+        time,
+    }
+}
 
 fn main() {
-    let input = fs::read_to_string("./example.bs").unwrap();
-    let tokens = tokenize(&input);
-    let ast = match parser::parse(tokens) {
-        Ok(e) => Ast::new(e),
+    let options = parse_options(std::env::args().skip(1));
+    let color = options.color;
+    if let Command::Repl = options.command {
+        if let Err(e) = repl::run(color) {
+            eprintln!("repl failed: {}", e);
+            process::exit(EXIT_USAGE);
+        }
+        return;
+    }
+
+    let mut sources = SourceMap::new();
+    let file = match sources.load(&options.path) {
+        Ok(f) => f,
         Err(e) => {
-            println!("{}", e.format_with(&input, "parse error", true));
-            process::exit(1);
+            eprintln!("could not read {}: {}", options.path, e);
+            process::exit(EXIT_USAGE);
         }
     };
+    let input = sources.source(file).unwrap_or_default();
+    let tokens = match tokenize_file(input, file) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e.format_with(&sources, "parse error", color));
+            process::exit(EXIT_PARSE);
+        }
+    };
+    let (nodes, errors) = parser::parse(tokens);
+    if !errors.is_empty() {
+        for e in errors.iter() {
+            eprintln!("{}\n", e.format_with(&sources, "parse error", color));
+        }
+        eprintln!("could not parse, {} error(s) found", errors.len());
+        process::exit(EXIT_PARSE);
+    }
+    let ast = Ast::new(nodes);
+
+    if let Command::Check = options.command {
+        let errors = executor::check(&ast);
+        for e in errors.iter() {
+            eprintln!("{}\n", e.format_with(&sources, "check error", color));
+        }
+        if !errors.is_empty() {
+            eprintln!("check failed, {} error(s) found", errors.len());
+            process::exit(EXIT_BUILD);
+        }
+        println!("{}: no errors found", options.path);
+        return;
+    }
+
     let executor = match Executor::build(ast) {
         Ok(e) => e,
         Err(e) => {
-            println!("at build: {}", e.format_with(&input, "build error", true));
-            process::exit(1);
+            eprintln!("{}", e.format_with(&sources, "build error", color));
+            process::exit(EXIT_BUILD);
         }
     };
-
+    let arguments: Vec<&str> = options.arguments.iter().map(String::as_str).collect();
+    let arguments = arguments.iter().map(|a| a as &dyn IntoValue).collect();
+    // building runs the top level statements, only the entry function is timed
     let time = Instant::now();
-    let result = executor.execute("main", vec![&["hello", "world"]]);
+    let result = executor.execute(&options.entry, arguments);
+    if options.time {
+        eprintln!("execution took: {:?}", time.elapsed());
+    }
     match result {
-        Ok(r) => println!("result: {:?}", r),
-        Err(e) => println!("{}", e.format_with(&input, "execution error", true)),
+        Ok(bs::data::Value::Unit) => {}
+        Ok(r) => println!("{}", r.repr()),
+        Err(e) => {
+            eprintln!("{}", e.format_with(&sources, "execution error", color));
+            process::exit(EXIT_RUNTIME);
+        }
     }
-    println!("execution took: {:?}", time.elapsed());
 }
@@ -1,23 +1,25 @@
 use crate::{error::ParseError, lexer::TokenStream};
 
-pub fn any<O>(
-    function: &[fn(&mut TokenStream, depth: u32) -> Result<O, ParseError>],
-    input: &mut TokenStream,
-    depth: u32,
-) -> Result<O, ParseError> {
-    let mut errors = Vec::new();
-    for f in function.into_iter() {
+pub type ParseFn<O> = fn(&mut TokenStream) -> Result<O, ParseError>;
+
+/// tries every function in order and returns the first success,
+/// if all fail, their errors are merged, see `ParseError::merge`
+pub fn any<O>(function: &[ParseFn<O>], input: &mut TokenStream) -> Result<O, ParseError> {
+    let mut error: Option<ParseError> = None;
+    for f in function.iter() {
         let pointer = input.pointer();
-        match f(input, depth) {
+        match f(input) {
             Ok(o) => return Ok(o),
             Err(e) => {
                 // reset
                 input.set_pointer(pointer);
-                errors.push(e);
+                input.record_failure(e.clone());
+                error = Some(match error {
+                    Some(error) => error.merge(e),
+                    None => e,
+                });
             }
         }
     }
-    errors.sort_unstable_by_key(|e| e.depth);
-    errors.reverse();
-    Err(errors.remove(0))
+    Err(error.unwrap_or_else(|| input.unexpected(Vec::new())))
 }
@@ -1,7 +1,7 @@
 pub mod parse;
 pub use parse::parse;
 
-use crate::{data, lexer::TokenKind};
+use crate::{data, error::ParseError, lexer::TokenKind, span::Span};
 use data::DataType;
 
 mod combinator;
@@ -10,47 +10,241 @@ mod combinator;
 pub enum Data {
     Base(DataType),
     Array(Vec<AstNode>),
+    /// `[element; count]`, the element is evaluated once
+    Repeat { element: Box<AstNode>, count: usize },
 }
 
 #[derive(Clone, Debug)]
 pub struct FnArgument {
     pub name: String,
     pub data_type: String,
+    /// `name: data_type = default`, evaluated by every call that leaves the parameter out,
+    /// after the parameters before it are bound
+    pub default: Option<AstNode>,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// an argument at a call site, `name: value` if it is named
+#[derive(Clone, Debug)]
+pub struct CallArgument {
+    pub name: Option<String>,
+    pub value: AstNode,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BinaryOperator {
     Add,
     Sub,
     Mul,
     Div,
+    Rem,
+    Pow,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
 }
 impl BinaryOperator {
     pub fn from_tokenkind(t: &TokenKind) -> Option<Self> {
-        match t {
-            &TokenKind::Plus => Some(Self::Add),
-            &TokenKind::Minus => Some(Self::Sub),
-            &TokenKind::Star => Some(Self::Mul),
-            &TokenKind::Slash => Some(Self::Div),
+        match *t {
+            TokenKind::Plus => Some(Self::Add),
+            TokenKind::Minus => Some(Self::Sub),
+            TokenKind::Star => Some(Self::Mul),
+            TokenKind::Slash => Some(Self::Div),
+            TokenKind::Percent => Some(Self::Rem),
+            TokenKind::StarStar => Some(Self::Pow),
+            TokenKind::Ampersand => Some(Self::BitAnd),
+            TokenKind::Pipe => Some(Self::BitOr),
+            TokenKind::Caret => Some(Self::BitXor),
+            TokenKind::LessLess => Some(Self::Shl),
+            TokenKind::GreaterGreater => Some(Self::Shr),
+            TokenKind::AmpersandAmpersand => Some(Self::And),
+            TokenKind::PipePipe => Some(Self::Or),
+            TokenKind::EqualEqual => Some(Self::Eq),
+            TokenKind::BangEqual => Some(Self::Ne),
+            TokenKind::Less => Some(Self::Lt),
+            TokenKind::LessEqual => Some(Self::Le),
+            TokenKind::Greater => Some(Self::Gt),
+            TokenKind::GreaterEqual => Some(Self::Ge),
+            _ => None,
+        }
+    }
+
+    /// the operator of a compound assignment like `+=`
+    pub fn from_assignment(t: &TokenKind) -> Option<Self> {
+        match *t {
+            TokenKind::PlusEqual => Some(Self::Add),
+            TokenKind::MinusEqual => Some(Self::Sub),
+            TokenKind::StarEqual => Some(Self::Mul),
+            TokenKind::SlashEqual => Some(Self::Div),
+            TokenKind::PercentEqual => Some(Self::Rem),
             _ => None,
         }
     }
+
+    /// every token that is a binary operator
+    pub fn tokens() -> Vec<TokenKind> {
+        vec![
+            TokenKind::Plus,
+            TokenKind::Minus,
+            TokenKind::Star,
+            TokenKind::Slash,
+            TokenKind::Percent,
+            TokenKind::StarStar,
+            TokenKind::Ampersand,
+            TokenKind::Pipe,
+            TokenKind::Caret,
+            TokenKind::LessLess,
+            TokenKind::GreaterGreater,
+            TokenKind::AmpersandAmpersand,
+            TokenKind::PipePipe,
+            TokenKind::EqualEqual,
+            TokenKind::BangEqual,
+            TokenKind::Less,
+            TokenKind::LessEqual,
+            TokenKind::Greater,
+            TokenKind::GreaterEqual,
+        ]
+    }
+
+    /// higher binds tighter, the levels follow rust:
+    /// `||` < `&&` < comparisons < `|` < `^` < `&` < `<< >>` < `+ -` < `* / %` < `**`
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge => 3,
+            Self::BitOr => 4,
+            Self::BitXor => 5,
+            Self::BitAnd => 6,
+            Self::Shl | Self::Shr => 7,
+            Self::Add | Self::Sub => 8,
+            Self::Mul | Self::Div | Self::Rem => 9,
+            Self::Pow => 10,
+        }
+    }
+
+    /// `2 ** 3 ** 2` is `2 ** (3 ** 2)`, all other operators are left associative
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Pow)
+    }
+}
+impl std::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+            Self::Pow => "**",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        };
+        write!(f, "{}", symbol)
+    }
 }
 #[derive(Clone, Debug)]
 pub struct AstNode {
     pub data: AstNodeData,
-    pub index: usize,
+    pub span: Span,
 }
 impl AstNode {
-    pub fn new(data: AstNodeData, index: usize) -> Self {
-        Self { data, index }
+    pub fn new(data: AstNodeData, span: Span) -> Self {
+        Self { data, span }
+    }
+
+    /// block like expressions end in a block and do not need a semicolon to be a statement
+    pub fn is_block_like(&self) -> bool {
+        matches!(
+            self.data,
+            AstNodeData::Block { .. }
+                | AstNodeData::IfStatement { .. }
+                | AstNodeData::FnDeclaration { .. }
+        )
+    }
+
+    /// direct child nodes, in source order
+    pub fn children(&self) -> Vec<&AstNode> {
+        match &self.data {
+            AstNodeData::Block { block, tail } => block.iter().chain(tail.as_deref()).collect(),
+            AstNodeData::Wrap { wrap } => vec![wrap],
+            AstNodeData::FnDeclaration {
+                arguments, body, ..
+            } => arguments
+                .iter()
+                .filter_map(|a| a.default.as_ref())
+                .chain([&**body])
+                .collect(),
+            AstNodeData::FnCall { arguments, .. } => arguments.iter().map(|a| &a.value).collect(),
+            AstNodeData::VarDeclaration { value, .. } => vec![value],
+            AstNodeData::VarAssign { value, .. } => vec![value],
+            AstNodeData::BinaryOperation { left, right, .. } => vec![left, right],
+            AstNodeData::Not { value } => vec![value],
+            AstNodeData::IfStatement {
+                condition,
+                when,
+                unless,
+            } => {
+                let mut children = vec![&**condition, &**when];
+                children.extend(unless.as_deref());
+                children
+            }
+            AstNodeData::Return { value } => value.as_deref().into_iter().collect(),
+            AstNodeData::Data {
+                data: Data::Array(array),
+            } => array.iter().collect(),
+            AstNodeData::Data {
+                data: Data::Repeat { element, .. },
+            } => vec![element],
+            AstNodeData::Interpolation { parts } => parts.iter().collect(),
+            AstNodeData::Identifier { .. }
+            | AstNodeData::Data {
+                data: Data::Base(_),
+            }
+            | AstNodeData::Invalid { .. } => Vec::new(),
+        }
+    }
+
+    /// every error of statements the parser recovered from
+    pub fn errors(&self) -> Vec<ParseError> {
+        match &self.data {
+            AstNodeData::Invalid { error } => vec![error.clone()],
+            _ => self
+                .children()
+                .into_iter()
+                .flat_map(|child| child.errors())
+                .collect(),
+        }
     }
 }
 
 #[derive(Clone, Debug)]
 pub enum AstNodeData {
+    /// evaluates to `tail`, the last expression if it is not followed by a semicolon,
+    /// or to unit
     Block {
         block: Vec<AstNode>,
+        tail: Option<Box<AstNode>>,
     },
     Wrap {
         wrap: Box<AstNode>,
@@ -61,13 +255,16 @@ pub enum AstNodeData {
         returns: Option<String>,
         body: Box<AstNode>,
     },
+    /// positional arguments come before named ones
     FnCall {
         name: String,
-        arguments: Vec<AstNode>,
+        arguments: Vec<CallArgument>,
     },
+    /// `let name = value`, or `const name = value` if `constant`, which cannot be assigned to
     VarDeclaration {
         name: String,
         value: Box<AstNode>,
+        constant: bool,
     },
     VarAssign {
         name: String,
@@ -75,9 +272,15 @@ pub enum AstNodeData {
     },
     BinaryOperation {
         operator: BinaryOperator,
+        /// span of the operator token, runtime errors of the operation point here
+        operator_span: Span,
         left: Box<AstNode>,
         right: Box<AstNode>,
     },
+    /// `!value`, negates a bool
+    Not {
+        value: Box<AstNode>,
+    },
     IfStatement {
         condition: Box<AstNode>,
         when: Box<AstNode>,
@@ -92,6 +295,14 @@ pub enum AstNodeData {
     Data {
         data: Data,
     },
+    /// interpolated string, the values of all parts are concatenated
+    Interpolation {
+        parts: Vec<AstNode>,
+    },
+    /// statement or value that failed to parse, the parser recovered after it
+    Invalid {
+        error: ParseError,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -1,81 +1,156 @@
-use crate::data::DataType;
-use crate::error::{ParseError, ParseErrorExt};
-use crate::lexer::{TokenKind, TokenStream};
-
-use super::combinator::any;
-use super::{AstNode, AstNodeData, BinaryOperator, Data as AstData, FnArgument};
-
-pub fn parse(mut input: TokenStream) -> Result<Vec<AstNode>, ParseError> {
+use crate::data::{DataType, MAX_LENGTH};
+use crate::diagnostic::ErrorCode;
+use crate::error::ParseError;
+use crate::span::Span;
+use crate::lexer::{StringPart, TokenKind, TokenStream};
+
+use super::combinator::{any, ParseFn};
+use super::{AstNode, AstNodeData, BinaryOperator, CallArgument, Data as AstData, FnArgument};
+
+/// parses as much as possible, statements with syntax errors are skipped,
+/// so the returned nodes are only complete if there are no errors
+pub fn parse(mut input: TokenStream) -> (Vec<AstNode>, Vec<ParseError>) {
     let mut nodes = Vec::new();
+    let mut errors = Vec::new();
 
     while !input.is_empty() {
-        nodes.push(node(&mut input, 0)?);
-        input.skip_if(&TokenKind::Semicolon);
+        let pointer = input.pointer();
+        input.reset_failures();
+        match node(&mut input) {
+            Ok(node) => {
+                errors.extend(node.errors());
+                let terminated =
+                    input.skip_if(&TokenKind::Semicolon).is_some() || node.is_block_like();
+                nodes.push(node);
+                if terminated {
+                    continue;
+                }
+                let missing = input.unexpected(vec![TokenKind::Semicolon]);
+                let error = input.furthest_failure(missing.clone());
+                // same as in blocks, a longer alternative failed inside the statement, skip
+                // its rest, otherwise continue as if the semicolon was there
+                if error.span.start > missing.span.start {
+                    input.synchronize();
+                }
+                errors.push(error);
+            }
+            Err(e) => {
+                errors.push(input.furthest_failure(e));
+                input.set_pointer(pointer);
+                input.synchronize();
+                // a stray '}' cannot be skipped by synchronizing
+                if input.pointer() == pointer {
+                    input.advance(1);
+                }
+            }
+        }
     }
 
-    Ok(nodes)
+    (nodes, errors)
 }
 
 trait ParseFunction {
     fn name(&self) -> &'static str;
-    fn func(&self) -> fn(&mut TokenStream, depth: u32) -> Result<AstNode, ParseError>;
+    fn func(&self) -> ParseFn<AstNode>;
 }
 
-fn node_filter(
-    input: &mut TokenStream,
-    filter: &[&'static str],
-    depth: u32,
-) -> Result<AstNode, ParseError> {
+fn node_filter(input: &mut TokenStream, filter: &[&'static str]) -> Result<AstNode, ParseError> {
     // order matters
     let mut fns: Vec<&dyn ParseFunction> = vec![
         &Binary {},
         &FnDeclaration {},
+        &If {},
         &FnCall {},
         &Block {},
         &Wrap {},
         &VarDeclaration {},
         &VarAssign {},
         &Return {},
+        &Not {},
         &Data {},
         &Identifier {},
     ];
     fns.retain(|func| !filter.contains(&func.name()));
     let functions: Vec<_> = fns.iter().map(|f| f.func()).collect();
 
-    any(&functions, input, depth)
+    any(&functions, input)
 }
 
-fn node(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-    node_filter(input, &[], depth)
+fn node(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+    node_filter(input, &[])
+}
+
+/// parse functions of statements that have no value
+const STATEMENTS: [&str; 4] = ["fn_declaration", "var_declaration", "var_assign", "return"];
+
+/// a node with a value, so statements are not expected where only expressions fit
+fn expression(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+    node_filter(input, &STATEMENTS)
 }
 
 struct Binary {}
 impl Binary {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
         let mut tmp_input = input.clone();
-        let left = Box::new(node_filter(&mut tmp_input, &["binary"], depth)?);
-        // let index = input[0].index;
-        let index = tmp_input.get_current_index().idc()?;
+        let left = Self::operand(&mut tmp_input)?;
+        // single operands are left to the other parse functions
+        if Self::peek_operator(&tmp_input).is_none() {
+            return Err(tmp_input.unexpected(BinaryOperator::tokens()));
+        }
+        let node = Self::climb(&mut tmp_input, left, 0)?;
 
-        let op = tmp_input.next().idc()?;
-        let operator = BinaryOperator::from_tokenkind(&op.kind).ok_or(ParseError::new(
-            index,
-            depth + 1,
-            format!("invalid operator: {:?}", op.kind),
-        ))?;
+        *input = tmp_input;
+        Ok(node)
+    }
 
-        let right = Box::new(node(&mut tmp_input, depth + 2)?);
+    fn operand(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let mut filter = STATEMENTS.to_vec();
+        filter.push("binary");
+        node_filter(input, &filter)
+    }
 
-        *input = tmp_input;
+    fn peek_operator(input: &TokenStream) -> Option<(BinaryOperator, Span)> {
+        let token = input.peek(0)?;
+        Some((BinaryOperator::from_tokenkind(&token.kind)?, token.span))
+    }
 
-        Ok(AstNode::new(
-            AstNodeData::BinaryOperation {
-                operator,
-                left,
-                right,
-            },
-            index,
-        ))
+    /// precedence climbing, folds all following operators binding at least as tight as
+    /// `min_precedence` into `left`
+    fn climb(
+        input: &mut TokenStream,
+        mut left: AstNode,
+        min_precedence: u8,
+    ) -> Result<AstNode, ParseError> {
+        while let Some((operator, operator_span)) = Self::peek_operator(input) {
+            let precedence = operator.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            input.advance(1);
+
+            let mut right = Self::operand(input)?;
+            while let Some((next, _)) = Self::peek_operator(input) {
+                if next.precedence() > precedence {
+                    right = Self::climb(input, right, precedence + 1)?;
+                } else if next.precedence() == precedence && next.is_right_associative() {
+                    right = Self::climb(input, right, precedence)?;
+                } else {
+                    break;
+                }
+            }
+
+            let span = left.span.to(right.span);
+            left = AstNode::new(
+                AstNodeData::BinaryOperation {
+                    operator,
+                    operator_span,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                span,
+            );
+        }
+        Ok(left)
     }
 }
 impl ParseFunction for Binary {
@@ -83,42 +158,96 @@ impl ParseFunction for Binary {
         "binary"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
+        Self::parse
+    }
+}
+
+struct Not {}
+impl Not {
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::Bang)?.span;
+        // binds tighter than every binary operator, `!a && b` is `(!a) && b`
+        let value = Box::new(Binary::operand(input)?);
+        let span = start.to(value.span);
+        Ok(AstNode::new(AstNodeData::Not { value }, span))
+    }
+}
+impl ParseFunction for Not {
+    fn name(&self) -> &'static str {
+        "not"
+    }
+
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct Block {}
 impl Block {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        input.skip_if(&TokenKind::LeftBrace).idc()?;
-        let index = input.get_current_index().idc()?;
-        let inner = input
-            .peek_pair_counting_stripped_inclusive(&TokenKind::LeftBrace, &TokenKind::RightBrace)
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::LeftBrace)?.span;
+        let mut inner = input
+            .inner_stream(&TokenKind::LeftBrace, &TokenKind::RightBrace)
             .ok_or(ParseError::new(
-                index,
-                depth + 1,
-                format!("missing closing '}}' delimiter"),
+                start,
+                "missing closing '}' delimiter".to_string(),
             ))?;
         let inner_len = inner.len();
         let mut nodes = Vec::new();
-        let mut inner = TokenStream::new(inner);
-        let mut depth = depth + 1;
+        let mut tail = None;
         while inner.skip_if(&TokenKind::RightBrace).is_none() {
-            let index = inner.get_current_index().idc()?;
-            let n = node(&mut inner, depth)?;
-            nodes.push(n.clone());
-            inner.skip_if(&TokenKind::Semicolon).ok_or(ParseError::new(
-                index,
-                depth,
-                format!("expected semicolon at the end"),
-            ))?;
-            depth += 1;
+            let pointer = inner.pointer();
+            inner.reset_failures();
+            match node(&mut inner) {
+                Ok(n) => {
+                    if inner.skip_if(&TokenKind::Semicolon).is_some() {
+                        nodes.push(n);
+                        continue;
+                    }
+                    // the last expression without a semicolon is the value of the block
+                    if inner.peek(0).map(|t| &t.kind) == Some(&TokenKind::RightBrace) {
+                        tail = Some(Box::new(n));
+                        continue;
+                    }
+                    if n.is_block_like() {
+                        nodes.push(n);
+                        continue;
+                    }
+                    let span = n.span;
+                    nodes.push(n);
+                    let missing = inner.unexpected(vec![TokenKind::Semicolon]);
+                    let error = inner.furthest_failure(missing.clone());
+                    // a longer alternative failed inside the statement, skip its rest,
+                    // otherwise continue as if the semicolon was there
+                    if error.span.start > missing.span.start {
+                        inner.synchronize();
+                    }
+                    nodes.push(AstNode::new(AstNodeData::Invalid { error }, span));
+                }
+                Err(error) => {
+                    let error = inner.furthest_failure(error);
+                    let span = error.span;
+                    nodes.push(AstNode::new(AstNodeData::Invalid { error }, span));
+                    inner.set_pointer(pointer);
+                    inner.synchronize();
+                    if inner.is_empty() {
+                        break;
+                    }
+                }
+            }
         }
         // only if successfull
         input.advance(inner_len);
 
-        Ok(AstNode::new(AstNodeData::Block { block: nodes }, index))
+        let span = input.span_from(start);
+        Ok(AstNode::new(
+            AstNodeData::Block {
+                block: nodes,
+                tail,
+            },
+            span,
+        ))
     }
 }
 impl ParseFunction for Block {
@@ -126,28 +255,31 @@ impl ParseFunction for Block {
         "block"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct Wrap {}
 impl Wrap {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let index = input.peek(0).idc()?.index;
-        input.skip_if(&TokenKind::LeftParen).idc()?;
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::LeftParen)?.span;
 
-        let inner = input
-            .peek_pair_counting_stripped_inclusive(&TokenKind::LeftParen, &TokenKind::RightParen)
-            .idc()?;
+        let mut inner = input
+            .inner_stream(&TokenKind::LeftParen, &TokenKind::RightParen)
+            .ok_or(ParseError::new(
+                start,
+                "missing closing ')' delimiter".to_string(),
+            ))?;
         let inner_len = inner.len();
-        let mut stream = TokenStream::new(inner);
-        let node = Box::new(node(&mut stream, depth)?);
+        let node = Box::new(expression(&mut inner)?);
+        inner.expect(&TokenKind::RightParen)?;
 
         // only if successfull
         input.advance(inner_len);
 
-        Ok(AstNode::new(AstNodeData::Wrap { wrap: node }, index))
+        let span = input.span_from(start);
+        Ok(AstNode::new(AstNodeData::Wrap { wrap: node }, span))
     }
 }
 impl ParseFunction for Wrap {
@@ -155,97 +287,95 @@ impl ParseFunction for Wrap {
         "wrap"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct FnDeclaration {}
 impl FnDeclaration {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let index = input.peek(0).idc()?.index;
-        input.skip_if(&TokenKind::Fn).idc()?;
-
-        let peek = input.peek(0).idc()?.clone();
-        (peek.kind == TokenKind::Identifier)
-            .then(|| input.advance(1))
-            .ok_or(ParseError::new(
-                index,
-                depth + 1,
-                format!("expected identifier, but found: {:?}", peek.kind),
-            ))?;
-        let Some(DataType::String(name)) = peek.data else {
-            return Err(ParseError::new(index, 2, format!("Identifier has no data")));
-        };
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::Fn)?.span;
+        let (name, _) = input.expect_identifier()?;
         let arguments = Self::parse_fn_arguments(input)?;
-        let returns = Self::parse_fn_return(input);
-        let pre_body_index = input.get_current_index().idc()?;
+        let returns = Self::parse_fn_return(input)?;
+        // the declaration spans the signature only, not the body
+        let span = input.span_from(start);
 
-        let body = Box::new(node(input, depth)?);
+        let body = Box::new(node(input)?);
         match body.data {
             AstNodeData::Wrap { .. } | AstNodeData::Block { .. } => (),
             _ => {
                 return Err(ParseError::new(
-                    pre_body_index,
-                    depth + 3,
-                    format!("function body must either be a block or wrap"),
+                    body.span,
+                    "function body must either be a block or wrap".to_string(),
                 ));
             }
         };
 
-        return Ok(AstNode::new(
+        Ok(AstNode::new(
             AstNodeData::FnDeclaration {
                 name,
                 arguments,
                 returns,
                 body,
             },
-            pre_body_index,
-        ));
+            span,
+        ))
     }
-    fn parse_fn_return(input: &mut TokenStream) -> Option<String> {
-        input.skip_if(&TokenKind::Arrow)?;
-
-        let peek = input.peek(0)?.clone();
-        (peek.kind == TokenKind::Identifier).then(|| input.advance(1))?;
-        match peek.data? {
-            DataType::String(s) => Some(s),
-            _ => None,
+    fn parse_fn_return(input: &mut TokenStream) -> Result<Option<String>, ParseError> {
+        if input.skip_if(&TokenKind::Arrow).is_none() {
+            return Ok(None);
         }
+        let (returns, _) = input.expect_identifier()?;
+        Ok(Some(returns))
     }
 
     fn parse_fn_arguments(input: &mut TokenStream) -> Result<Vec<FnArgument>, ParseError> {
-        input.skip_if(&TokenKind::LeftParen).idc()?;
+        input.expect(&TokenKind::LeftParen)?;
         let mut arguments = Vec::new();
-        while let Some(argument) = parse_fn_inner(input) {
-            arguments.push(argument)
+        // a trailing comma is allowed
+        while input.skip_if(&TokenKind::RightParen).is_none() {
+            if input.peek(0).map(|t| &t.kind) != Some(&TokenKind::Identifier) {
+                return Err(input.unexpected(vec![TokenKind::Identifier, TokenKind::RightParen]));
+            }
+            let start = input.peek(0).map(|t| t.span).unwrap_or_default();
+            let argument = parse_fn_inner(input)?;
+            let follows_default = arguments.last().is_some_and(|a: &FnArgument| a.default.is_some());
+            if follows_default && argument.default.is_none() {
+                return Err(ParseError::new(
+                    input.span_from(start),
+                    format!("parameter '{}' needs a default value", argument.name),
+                )
+                .with_note(
+                    "parameters without a default must come before those with one".to_string(),
+                ));
+            }
+            arguments.push(argument);
+            if input.skip_if(&TokenKind::Comma).is_none() {
+                input
+                    .expect(&TokenKind::RightParen)
+                    .map_err(|_| input.unexpected(vec![TokenKind::Comma, TokenKind::RightParen]))?;
+                break;
+            }
         }
-        input.skip_if(&TokenKind::RightParen).idc()?;
 
         return Ok(arguments);
 
-        fn parse_fn_inner(input: &mut TokenStream) -> Option<FnArgument> {
-            let peek = input.peek(0)?.clone();
-            (peek.kind == TokenKind::Identifier).then(|| input.advance(1))?;
-            let name = match peek.data? {
-                DataType::String(s) => Some(s),
-                _ => None,
-            }?;
-
-            // (input.peek(0)?.kind == TokenKind::Colon).then(|| input.advance(1))?;
-            input.skip_if(&TokenKind::Colon)?;
+        fn parse_fn_inner(input: &mut TokenStream) -> Result<FnArgument, ParseError> {
+            let (name, _) = input.expect_identifier()?;
+            input.expect(&TokenKind::Colon)?;
+            let (data_type, _) = input.expect_identifier()?;
+            let default = match input.skip_if(&TokenKind::Equal) {
+                Some(_) => Some(expression(input)?),
+                None => None,
+            };
 
-            let peek = input.peek(0)?.clone();
-            (peek.kind == TokenKind::Identifier).then(|| input.advance(1))?;
-            let data_type = match peek.data? {
-                DataType::String(s) => Some(s),
-                _ => None,
-            }?;
-
-            // no ? at end, does not need to occur, but if it occurs do not fail but skip
-            (input.peek(0)?.kind == TokenKind::Comma).then(|| input.advance(1));
-
-            Some(FnArgument { name, data_type })
+            Ok(FnArgument {
+                name,
+                data_type,
+                default,
+            })
         }
     }
 }
@@ -254,48 +384,99 @@ impl ParseFunction for FnDeclaration {
         "fn_declaration"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
+        Self::parse
+    }
+}
+
+struct If {}
+impl If {
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::If)?.span;
+        let condition = Box::new(expression(input)?);
+        let when = Box::new(Block::parse(input)?);
+        let unless = match input.skip_if(&TokenKind::Else) {
+            // `else if` chains
+            Some(_) => Some(Box::new(any(&[If::parse, Block::parse], input)?)),
+            None => None,
+        };
+
+        let span = input.span_from(start);
+        Ok(AstNode::new(
+            AstNodeData::IfStatement {
+                condition,
+                when,
+                unless,
+            },
+            span,
+        ))
+    }
+}
+impl ParseFunction for If {
+    fn name(&self) -> &'static str {
+        "if"
+    }
+
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct FnCall {}
 impl FnCall {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let peek = input.peek(0).idc()?.clone();
-        let index = peek.index;
-
-        (peek.kind == TokenKind::Identifier)
-            .then(|| input.advance(1))
-            .ok_or(ParseError::new(index, depth, format!("invalid identifier")))?;
-        let Some(DataType::String(name)) = peek.data else {
-            return Err(ParseError::new(
-                index,
-                depth,
-                format!("invalid fn call name"),
-            ));
-        };
-        let index = input.peek(0).idc()?.index;
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let (name, start) = input.expect_identifier()?;
 
-        // (input.peek(0)?.kind == TokenKind::LeftParen).then(|| input.advance(1))?;
-        input.skip_if(&TokenKind::LeftParen).idc()?;
-        let inner = input
-            .peek_pair_counting_stripped_inclusive(&TokenKind::LeftParen, &TokenKind::RightParen)
-            .idc()?;
+        let open = input.expect(&TokenKind::LeftParen)?.span;
+        let mut inner = input
+            .inner_stream(&TokenKind::LeftParen, &TokenKind::RightParen)
+            .ok_or(ParseError::new(
+                open,
+                "missing closing ')' delimiter".to_string(),
+            ))?;
         let inner_len = inner.len();
         let mut arguments = Vec::new();
-        let mut inner = TokenStream::new(inner);
-        loop {
-            arguments.push(node(&mut inner, depth)?);
+        while inner.skip_if(&TokenKind::RightParen).is_none() {
+            let start = inner.peek(0).map(|t| t.span).unwrap_or_default();
+            let argument = Self::parse_argument(&mut inner)?;
+            let follows_named = arguments.last().is_some_and(|a: &CallArgument| a.name.is_some());
+            if follows_named && argument.name.is_none() {
+                // the argument itself parsed, failures recorded while parsing it are irrelevant
+                inner.reset_failures();
+                return Err(ParseError::new(
+                    start.to(argument.value.span),
+                    "positional argument after a named argument".to_string(),
+                )
+                .with_note("named arguments must come after all positional ones".to_string()));
+            }
+            arguments.push(argument);
             if inner.skip_if(&TokenKind::Comma).is_none() {
-                inner.skip_if(&TokenKind::RightParen).idc()?;
+                inner
+                    .expect(&TokenKind::RightParen)
+                    .map_err(|_| inner.unexpected(vec![TokenKind::Comma, TokenKind::RightParen]))?;
                 break;
             }
         }
         input.advance(inner_len);
-        // input.skip_if(&TokenKind::RightParen)?;
 
-        Ok(AstNode::new(AstNodeData::FnCall { name, arguments }, index))
+        let span = input.span_from(start);
+        Ok(AstNode::new(AstNodeData::FnCall { name, arguments }, span))
+    }
+
+    /// `value` or `name: value`
+    fn parse_argument(input: &mut TokenStream) -> Result<CallArgument, ParseError> {
+        let named = input.peek(0).map(|t| &t.kind) == Some(&TokenKind::Identifier)
+            && input.peek(1).map(|t| &t.kind) == Some(&TokenKind::Colon);
+        let name = match named {
+            true => {
+                let (name, _) = input.expect_identifier()?;
+                input.expect(&TokenKind::Colon)?;
+                Some(name)
+            }
+            false => None,
+        };
+        let value = expression(input)?;
+        Ok(CallArgument { name, value })
     }
 }
 impl ParseFunction for FnCall {
@@ -303,25 +484,16 @@ impl ParseFunction for FnCall {
         "fncall"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct Identifier {}
 impl Identifier {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let peek = input.peek(0).idc()?.clone();
-        let index = peek.index;
-
-        // no proper error return, wanted!
-        (peek.kind == TokenKind::Identifier)
-            .then(|| input.advance(1))
-            .idc()?;
-        let Some(DataType::String(value)) = peek.data.clone() else {
-            return Err(ParseError::new(index, depth, format!("invalid identifier")));
-        };
-        return Ok(AstNode::new(AstNodeData::Identifier { value }, index));
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let (value, span) = input.expect_identifier()?;
+        Ok(AstNode::new(AstNodeData::Identifier { value }, span))
     }
 }
 impl ParseFunction for Identifier {
@@ -329,42 +501,50 @@ impl ParseFunction for Identifier {
         "identifier"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct Data {}
 impl Data {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        any(&[Self::parse_literals, Self::parse_array], input, depth)
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        any(&[Self::parse_literals, Self::parse_array], input)
     }
 
-    fn parse_literals(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let peek = input.peek(0).idc()?;
-        let index = peek.index;
+    fn parse_literals(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let Some(peek) = input.peek(0) else {
+            return Err(input.unexpected(Self::literals()));
+        };
+        let span = peek.span;
         match peek.kind {
             TokenKind::StringLiteral | TokenKind::FloatLiteral => {
-                let data = peek.data.clone().ok_or(ParseError::new(
-                    index,
-                    depth,
-                    format!("invalid data"),
-                ))?;
+                // the lexer leaves the data out of integers that do not fit
+                let data = peek.data.clone().ok_or(
+                    ParseError::new(span, "integer literal is too large".to_string())
+                        .with_code(ErrorCode::Overflow)
+                        .with_note(format!("integers range from {} to {}", i64::MIN, i64::MAX)),
+                )?;
                 input.advance(1);
                 return Ok(AstNode::new(
                     AstNodeData::Data {
                         data: AstData::Base(data),
                     },
-                    index,
+                    span,
                 ));
             }
+            TokenKind::InterpolatedString => {
+                let parts = peek.parts.clone();
+                input.advance(1);
+                return Self::parse_interpolation(parts, span);
+            }
             TokenKind::True => {
                 input.advance(1);
                 return Ok(AstNode::new(
                     AstNodeData::Data {
                         data: AstData::Base(DataType::Bool(true)),
                     },
-                    index,
+                    span,
                 ));
             }
             TokenKind::False => {
@@ -373,57 +553,114 @@ impl Data {
                     AstNodeData::Data {
                         data: AstData::Base(DataType::Bool(false)),
                     },
-                    index,
+                    span,
                 ));
             }
             _ => (),
         }
-        return Err(ParseError::new(index, 0, format!("invalid data")));
+        Err(input.unexpected(Self::literals()))
     }
 
-    fn parse_array(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let index = input.peek(0).idc()?.index;
-        input.skip_if(&TokenKind::LeftBracket).idc()?;
+    fn literals() -> Vec<TokenKind> {
+        vec![
+            TokenKind::StringLiteral,
+            TokenKind::InterpolatedString,
+            TokenKind::FloatLiteral,
+            TokenKind::True,
+            TokenKind::False,
+        ]
+    }
 
-        let inner = input
-            .peek_pair_counting_stripped_inclusive(
-                &TokenKind::LeftBracket,
-                &TokenKind::RightBracket,
-            )
-            .idc()?;
+    fn parse_interpolation(parts: Vec<StringPart>, span: Span) -> Result<AstNode, ParseError> {
+        let mut nodes = Vec::new();
+        for part in parts {
+            match part {
+                StringPart::Literal(s) => nodes.push(AstNode::new(
+                    AstNodeData::Data {
+                        data: AstData::Base(DataType::String(s)),
+                    },
+                    span,
+                )),
+                StringPart::Expression(tokens) => {
+                    let mut inner = TokenStream::from(tokens);
+                    nodes.push(expression(&mut inner)?);
+                    if !inner.is_empty() {
+                        return Err(inner.furthest_failure(inner.unexpected(Vec::new())));
+                    }
+                }
+            }
+        }
+
+        Ok(AstNode::new(AstNodeData::Interpolation { parts: nodes }, span))
+    }
+
+    fn parse_array(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::LeftBracket)?.span;
+
+        let mut inner = input
+            .inner_stream(&TokenKind::LeftBracket, &TokenKind::RightBracket)
+            .ok_or(ParseError::new(
+                start,
+                "missing closing ']' delimiter".to_string(),
+            ))?;
         let inner_len = inner.len();
         let mut array = Vec::new();
-        let mut inner = TokenStream::new(inner);
-        let mut depth = depth + 1;
-        while let Ok(node) = node(&mut inner, depth) {
-            array.push(node);
+        // a trailing comma is allowed
+        while inner.skip_if(&TokenKind::RightBracket).is_none() {
+            array.push(expression(&mut inner)?);
+            if inner.skip_if(&TokenKind::Comma).is_some() {
+                continue;
+            }
             // when true handle "[T; N]" case
-            if inner.skip_if(&TokenKind::Comma).is_none() {
-                if array.len() == 1 && inner.skip_if(&TokenKind::Semicolon).is_some() {
-                    let peek = inner.peek(0).idc()?.clone();
-                    (peek.kind == TokenKind::FloatLiteral)
-                        .then(|| inner.advance(1))
-                        .ok_or(ParseError::new(index, depth, format!("invalid data")))?;
-                    let Some(DataType::Float(value)) = peek.data.clone() else {
-                        return Err(ParseError::new(index, depth, format!("invalid data")));
-                    };
-                    let count = (value as i64 - 1).max(0);
-                    for _ in 0..count {
-                        array.push(array[0].clone());
-                    }
-                }
-                break;
+            if array.len() == 1 && inner.skip_if(&TokenKind::Semicolon).is_some() {
+                let count = inner.expect(&TokenKind::FloatLiteral)?;
+                let span = count.span;
+                let count = match count.data {
+                    Some(DataType::Int(value)) => value,
+                    Some(DataType::Float(value)) => value as i64,
+                    _ => return Err(ParseError::new(span, "invalid data".to_string())),
+                };
+                inner.expect(&TokenKind::RightBracket)?;
+                // the array itself is complete, parsing goes on after it in any case
+                input.advance(inner_len);
+                let data = match usize::try_from(count) {
+                    Ok(count) if count <= MAX_LENGTH => AstNodeData::Data {
+                        data: AstData::Repeat {
+                            element: Box::new(array.remove(0)),
+                            count,
+                        },
+                    },
+                    Ok(_) => AstNodeData::Invalid {
+                        error: ParseError::new(
+                            span,
+                            format!("array length is larger than {}", MAX_LENGTH),
+                        )
+                        .with_code(ErrorCode::TooLarge),
+                    },
+                    Err(_) => AstNodeData::Invalid {
+                        error: ParseError::new(span, "array length cannot be negative".to_string()),
+                    },
+                };
+                return Ok(AstNode::new(data, input.span_from(start)));
+            }
+            let mut expected = vec![TokenKind::Comma, TokenKind::RightBracket];
+            if array.len() == 1 {
+                expected.push(TokenKind::Semicolon);
             }
-            depth += 1;
+            inner
+                .expect(&TokenKind::RightBracket)
+                .map_err(|_| inner.unexpected(expected))?;
+            break;
         }
         // only if successfull
         input.advance(inner_len);
 
+        let span = input.span_from(start);
         Ok(AstNode::new(
             AstNodeData::Data {
                 data: AstData::Array(array),
             },
-            index,
+            span,
         ))
     }
 }
@@ -432,38 +669,24 @@ impl ParseFunction for Data {
         "data"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct Return {}
 impl Return {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let peek = input.peek(0).idc()?;
-        let index = peek.index;
-        (peek.kind == TokenKind::Return)
-            .then(|| input.advance(1))
-            .ok_or(ParseError::new(index, depth, format!("invalid data in r")))?;
-        let value = if let Ok(value) = node(input, depth) {
-            Some(Box::new(value))
-        } else {
-            None
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let start = input.expect(&TokenKind::Return)?.span;
+        let value = match input.peek(0).map(|t| &t.kind) {
+            Some(TokenKind::Semicolon) => None,
+            _ => Some(Box::new(expression(input)?)),
         };
-        let index = input.get_current_index().unwrap_or(0);
-        // input.skip_if(&TokenKind::Semicolon).ok_or(ParseError::new(
-        //     index,
-        //     depth + 1,
-        //     format!("invalid statement"),
-        // ))?;
-        if input.peek(0).idc()?.kind != TokenKind::Semicolon {
-            return Err(ParseError::new(
-                index,
-                depth + 1,
-                format!("invalid statement"),
-            ));
+        if input.peek(0).map(|t| &t.kind) != Some(&TokenKind::Semicolon) {
+            return Err(input.unexpected(vec![TokenKind::Semicolon]));
         }
-        return Ok(AstNode::new(AstNodeData::Return { value }, index));
+        let span = input.span_from(start);
+        Ok(AstNode::new(AstNodeData::Return { value }, span))
     }
 }
 impl ParseFunction for Return {
@@ -471,32 +694,32 @@ impl ParseFunction for Return {
         "return"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct VarDeclaration {}
 impl VarDeclaration {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let index = input.peek(0).idc()?.index;
-        input.skip_if(&TokenKind::Let).idc()?;
-        let peek = input.peek(0).idc()?.clone();
-        (peek.kind == TokenKind::Identifier)
-            .then(|| input.advance(1))
-            .ok_or(ParseError::new(index, 1, format!("invalid data")))?;
-        // if peek.kind == TokenKind::Identifier {
-        let Some(DataType::String(name)) = peek.data else {
-            return Err(ParseError::new(index, depth + 1, format!("invalid data")));
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let constant = matches!(input.peek(0), Some(token) if token.kind == TokenKind::Const);
+        let start = match input.peek(0) {
+            Some(token) if constant || token.kind == TokenKind::Let => token.span,
+            _ => return Err(input.unexpected(vec![TokenKind::Let, TokenKind::Const])),
         };
+        input.advance(1);
+        let (name, _) = input.expect_identifier()?;
+        input.expect(&TokenKind::Equal)?;
 
-        // (input.peek(0)?.kind == TokenKind::Equal).then(|| input.advance(1))?;
-        input.skip_if(&TokenKind::Equal).idc()?;
-
-        let value = Box::new(node(input, depth)?);
+        let value = Box::new(expression(input)?);
+        let span = start.to(value.span);
         Ok(AstNode::new(
-            AstNodeData::VarDeclaration { name, value },
-            index,
+            AstNodeData::VarDeclaration {
+                name,
+                value,
+                constant,
+            },
+            span,
         ))
     }
 }
@@ -505,29 +728,56 @@ impl ParseFunction for VarDeclaration {
         "var_declaration"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
 
 struct VarAssign {}
 impl VarAssign {
-    fn parse(input: &mut TokenStream, depth: u32) -> Result<AstNode, ParseError> {
-        let index = input.peek(0).idc()?.index;
-        let peek = input.peek(0).idc()?.clone();
-        (peek.kind == TokenKind::Identifier)
-            .then(|| input.advance(1))
-            .ok_or(ParseError::new(index, depth, format!("invalid data")))?;
-        // if peek.kind == TokenKind::Identifier {
-        let Some(DataType::String(name)) = peek.data else {
-            return Err(ParseError::new(index, depth, format!("invalid data")));
+    fn parse(input: &mut TokenStream) -> Result<AstNode, ParseError> {
+        let (name, start) = input.expect_identifier()?;
+        // `x += 1` is parsed as `x = x + 1`
+        let compound = match input.peek(0) {
+            Some(token) => BinaryOperator::from_assignment(&token.kind).map(|op| (op, token.span)),
+            None => None,
         };
+        match compound {
+            Some(_) => input.advance(1),
+            None => {
+                input.expect(&TokenKind::Equal).map_err(|_| {
+                    input.unexpected(vec![
+                        TokenKind::Equal,
+                        TokenKind::PlusEqual,
+                        TokenKind::MinusEqual,
+                        TokenKind::StarEqual,
+                        TokenKind::SlashEqual,
+                        TokenKind::PercentEqual,
+                    ])
+                })?;
+            }
+        }
 
-        // (input.peek(0)?.kind == TokenKind::Equal).then(|| input.advance(1))?;
-        input.skip_if(&TokenKind::Equal).idc()?;
-
-        let value = Box::new(node(input, depth)?);
-        Ok(AstNode::new(AstNodeData::VarAssign { name, value }, index))
+        let mut value = Box::new(expression(input)?);
+        let span = start.to(value.span);
+        if let Some((operator, operator_span)) = compound {
+            let target = AstNode::new(
+                AstNodeData::Identifier {
+                    value: name.clone(),
+                },
+                start,
+            );
+            value = Box::new(AstNode::new(
+                AstNodeData::BinaryOperation {
+                    operator,
+                    operator_span,
+                    left: Box::new(target),
+                    right: value,
+                },
+                span,
+            ));
+        }
+        Ok(AstNode::new(AstNodeData::VarAssign { name, value }, span))
     }
 }
 impl ParseFunction for VarAssign {
@@ -535,7 +785,7 @@ impl ParseFunction for VarAssign {
         "var_assign"
     }
 
-    fn func(&self) -> fn(&mut TokenStream, u32) -> Result<AstNode, ParseError> {
+    fn func(&self) -> ParseFn<AstNode> {
         Self::parse
     }
 }
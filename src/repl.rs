@@ -0,0 +1,173 @@
+use bs::data::Value;
+use bs::executor::Executor;
+use bs::lexer::{tokenize, tokenize_file, TokenKind};
+use bs::parser::{self, Ast};
+use bs::source::SourceMap;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+const HELP: &str = "\
+enter statements and expressions, the value of an expression is printed.
+input continues on the next line while braces, parentheses or brackets are open.
+
+commands:
+    :help         shows this message
+    :vars         lists the variables and their values
+    :funcs        lists the declared functions
+    :load <file>  runs a script, keeping its functions and variables
+    :reset        forgets every function and variable
+    :quit         exits, like ctrl-d";
+
+struct Repl {
+    executor: Executor,
+    sources: SourceMap,
+    color: bool,
+}
+impl Repl {
+    fn new(color: bool) -> Self {
+        Self {
+            executor: empty_executor(),
+            sources: SourceMap::new(),
+            color,
+        }
+    }
+
+    /// runs `source` in the persistent context and prints its value
+    fn eval(&mut self, path: String, source: String) {
+        let file = self.sources.add(path, source);
+        let input = self.sources.source(file).unwrap_or_default();
+        let tokens = match tokenize_file(input, file) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("{}", e.format_with(&self.sources, "parse error", self.color));
+                return;
+            }
+        };
+        let (nodes, errors) = parser::parse(tokens);
+        if !errors.is_empty() {
+            for e in errors.iter() {
+                eprintln!("{}", e.format_with(&self.sources, "parse error", self.color));
+            }
+            return;
+        }
+        match self.executor.eval(Ast::new(nodes)) {
+            Ok(Value::Unit) => {}
+            Ok(value) => println!("{}", value.repr()),
+            Err(e) => eprintln!("{}", e.format_with(&self.sources, "execution error", self.color)),
+        }
+    }
+
+    /// `false` if the session should end
+    fn command(&mut self, line: &str) -> bool {
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        match command {
+            ":help" => println!("{}", HELP),
+            ":vars" => {
+                for (name, value) in self.executor.globals() {
+                    println!("{} = {}", name, value.repr());
+                }
+            }
+            ":funcs" => {
+                let mut functions: Vec<_> = self.executor.context.functions.iter().collect();
+                functions.sort_by(|a, b| a.0.cmp(b.0));
+                for (name, function) in functions {
+                    let parameters: Vec<String> = function
+                        .arguments
+                        .iter()
+                        .map(|a| match a.default {
+                            Some(_) => format!("{}?: {}", a.name, a.data_type),
+                            None => format!("{}: {}", a.name, a.data_type),
+                        })
+                        .collect();
+                    match &function.returns {
+                        Some(returns) => println!("fn {}({}) -> {}", name, parameters.join(", "), returns),
+                        None => println!("fn {}({})", name, parameters.join(", ")),
+                    }
+                }
+            }
+            ":load" if argument.is_empty() => eprintln!("usage: :load <file>"),
+            ":load" => match std::fs::read_to_string(argument) {
+                Ok(source) => self.eval(argument.to_string(), source),
+                Err(e) => eprintln!("could not read {}: {}", argument, e),
+            },
+            ":reset" => self.executor = empty_executor(),
+            ":quit" => return false,
+            _ => eprintln!("unknown command '{}', see :help", command),
+        }
+        true
+    }
+}
+
+fn empty_executor() -> Executor {
+    Executor::build(Ast::new(Vec::new())).expect("an empty script always builds")
+}
+
+/// whether `source` has unclosed delimiters, so the input goes on in the next line.
+/// input the lexer rejects is complete, the error is reported when it is evaluated
+fn incomplete(source: &str) -> bool {
+    let Ok(tokens) = tokenize(source) else {
+        return false;
+    };
+    let mut depth = 0;
+    for token in tokens {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => depth += 1,
+            TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => depth -= 1,
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+/// statements need a `;`, which is optional for the last one of an input
+fn terminated(mut source: String) -> String {
+    let end = source.trim_end();
+    if !(end.ends_with(';') || end.ends_with('}')) {
+        source.push(';');
+    }
+    source
+}
+
+pub fn run(color: bool) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let mut repl = Repl::new(color);
+    let mut buffer = String::new();
+    let mut inputs = 0;
+    loop {
+        let prompt = if buffer.is_empty() { ">> " } else { ".. " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // ctrl-c discards the current input
+            Err(ReadlineError::Interrupted) => {
+                buffer.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if buffer.is_empty() && line.trim().starts_with(':') {
+            editor.add_history_entry(line.trim())?;
+            if !repl.command(line.trim()) {
+                return Ok(());
+            }
+            continue;
+        }
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(&line);
+        if incomplete(&buffer) {
+            continue;
+        }
+        let source = std::mem::take(&mut buffer);
+        if source.trim().is_empty() {
+            continue;
+        }
+        editor.add_history_entry(source.as_str())?;
+        inputs += 1;
+        repl.eval(format!("<repl:{}>", inputs), terminated(source));
+    }
+}
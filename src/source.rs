@@ -0,0 +1,135 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::span::{FileId, Span};
+
+/// a loaded script, offsets into `source` are byte offsets
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+    line_starts: Vec<usize>,
+}
+impl SourceFile {
+    pub fn new(path: PathBuf, source: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            path,
+            source,
+            line_starts,
+        }
+    }
+
+    /// zero based index of the line containing `offset`
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    /// byte offset of the line start and the line itself without its line break
+    fn line(&self, index: usize) -> (usize, &str) {
+        let start = self.line_starts[index];
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .map(|next| next - 1)
+            .unwrap_or(self.source.len());
+        let line = &self.source[start..end];
+        (start, line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// moves `offset` into the source and onto a char boundary
+    pub fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// 1-based line and column, the column counts chars, not bytes
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        let index = self.line_index(offset);
+        let start = self.line_starts[index];
+        let column = self.source[start..offset].chars().count() + 1;
+        (index + 1, column)
+    }
+
+    /// returns `(line number, byte offset of the line, line)` for every line touched by `span`,
+    /// at least the line containing `span.start` is returned
+    pub fn lines(&self, span: Span) -> Vec<(usize, usize, &str)> {
+        let start = self.clamp(span.start);
+        let end = self.clamp(span.end.max(span.start));
+        let first = self.line_index(start);
+        // a span ending right after a line break does not touch the next line
+        let last = self.line_index(end.saturating_sub(1).max(start));
+        (first..=last)
+            .map(|index| {
+                let (offset, line) = self.line(index);
+                (index + 1, offset, line)
+            })
+            .collect()
+    }
+}
+
+/// position of a span, displayed as `file:line:col`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// owns every loaded source file, spans without a file refer to the first one
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<PathBuf>, source: String) -> FileId {
+        self.files.push(SourceFile::new(path.into(), source));
+        self.files.len() - 1
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let source = std::fs::read_to_string(path.as_ref())?;
+        Ok(self.add(path.as_ref(), source))
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file)
+    }
+
+    pub fn source(&self, file: FileId) -> Option<&str> {
+        Some(self.get(file)?.source.as_str())
+    }
+
+    pub fn file_of(&self, span: Span) -> Option<&SourceFile> {
+        self.get(span.file.unwrap_or(0))
+    }
+
+    pub fn location(&self, span: Span) -> Option<Location> {
+        let file = self.file_of(span)?;
+        let (line, column) = file.line_col(span.start);
+        Some(Location {
+            path: file.path.clone(),
+            line,
+            column,
+        })
+    }
+}
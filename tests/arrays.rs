@@ -0,0 +1,79 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use common::{error_code, repr, run_main, run_source};
+
+#[test]
+fn changes_return_a_new_array() {
+    assert_eq!(repr("push([1], 2)"), "[1, 2]");
+    assert_eq!(repr("pop([1, 2])"), "2");
+    assert_eq!(repr("insert([1, 3], 1, 2)"), "[1, 2, 3]");
+    assert_eq!(repr("insert([1], 1, 2)"), "[1, 2]");
+    assert_eq!(repr("remove([1, 2, 3], 0)"), "[2, 3]");
+    assert_eq!(repr("concat([1], [], [2, 3])"), "[1, 2, 3]");
+    assert_eq!(repr("reverse([1, 2, 3])"), "[3, 2, 1]");
+    let value = run_main("let a = [1]; let b = push(a, 2); return [len(a), len(b)];").unwrap();
+    assert_eq!(value.repr(), "[1, 2]");
+    assert_eq!(error_code("remove([1], 1)"), Some(ErrorCode::InvalidValue));
+    assert_eq!(error_code("pop([])"), Some(ErrorCode::InvalidValue));
+}
+
+#[test]
+fn sorting_orders_types_and_numbers() {
+    assert_eq!(repr("sort([3, 1.5, 2])"), "[1.5, 2, 3]");
+    assert_eq!(
+        repr("sort([\"b\", [1], 2, true, \"a\"])"),
+        "[true, 2, \"a\", \"b\", [1]]"
+    );
+    // stable: equal numbers keep their order
+    assert_eq!(repr("sort([1.0, 1, 0])"), "[0, 1.0, 1]");
+    assert_eq!(repr("sort([[1, 2], [1], [0, 5]])"), "[[0, 5], [1], [1, 2]]");
+}
+
+#[test]
+fn searching() {
+    assert_eq!(repr("contains([1, 2], 2.0)"), "true");
+    assert_eq!(repr("contains(\"abc\", \"b\")"), "true");
+    assert_eq!(repr("index_of([\"a\", \"b\"], \"b\")"), "1");
+    assert_eq!(repr("index_of([1], 2)"), "-1");
+    assert_eq!(repr("len([1, [2, 3]])"), "2");
+}
+
+#[test]
+fn aggregates() {
+    assert_eq!(repr("sum([1, 2, 3])"), "6");
+    assert_eq!(repr("sum([1, 2.5])"), "3.5");
+    assert_eq!(repr("sum([[1, 2], [3, 4]])"), "[4, 6]");
+    assert_eq!(repr("sum([])"), "0");
+    assert_eq!(repr("product([2, 3, 4])"), "24");
+    assert_eq!(repr("min([3, 1, 2])"), "1");
+    assert_eq!(repr("max([\"a\", \"c\", \"b\"])"), "\"c\"");
+    assert_eq!(error_code("sum([true, false])"), Some(ErrorCode::InvalidOperands));
+    assert_eq!(error_code("max([])"), Some(ErrorCode::InvalidValue));
+}
+
+#[test]
+fn building_arrays() {
+    assert_eq!(repr("zip([1, 2, 3], [\"a\", \"b\"])"), "[[1, \"a\"], [2, \"b\"]]");
+    assert_eq!(repr("flatten([[1], 2, [3, [4]]])"), "[1, 2, 3, [4]]");
+    assert_eq!(repr("range(3)"), "[0, 1, 2]");
+    assert_eq!(repr("range(1, 4)"), "[1, 2, 3]");
+    assert_eq!(repr("range(5, 0, -2)"), "[5, 3, 1]");
+    assert_eq!(error_code("range(0, 5, 0)"), Some(ErrorCode::InvalidValue));
+    assert_eq!(repr("range(5, 0)"), "[]");
+    assert_eq!(repr("range(0, 7, 3)"), "[0, 3, 6]");
+    // too large to allocate, the host must not abort
+    assert_eq!(error_code("range(0, 10000000000)"), Some(ErrorCode::TooLarge));
+    assert_eq!(error_code("range(-9223372036854775807, 9223372036854775807, 2)"), Some(ErrorCode::TooLarge));
+}
+
+#[test]
+fn repeated_elements_are_evaluated_once() {
+    assert_eq!(repr("[1 + 1; 3]"), "[2, 2, 2]");
+    assert_eq!(repr("[[1, 2]; 2]"), "[[1, 2], [1, 2]]");
+    assert_eq!(repr("[\"a\"; 0]"), "[]");
+    let source = "fn main() { let a = [next(); 3]; return [a, count]; }
+let count = 0;
+fn next() { count = count + 1; return count; }";
+    assert_eq!(run_source(source).unwrap().repr(), "[[1, 1, 1], 1]");
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use bs::executor::MAX_CALL_DEPTH;
+use bs::source::SourceMap;
+use bs::span::Span;
+use common::{run_main, run_source};
+
+const SOURCE: &str = "\
+fn main() {
+    return outer();
+}
+fn outer() {
+    return inner(0);
+}
+fn inner(n: Int) {
+    return 1 / n;
+}";
+
+fn span(text: &str) -> Span {
+    let start = SOURCE.find(text).unwrap();
+    Span::new(start, start + text.len())
+}
+
+#[test]
+fn frames_list_the_calls_innermost_first() {
+    let error = run_source(SOURCE).unwrap_err();
+    let frames: Vec<_> = error.backtrace.iter().map(|f| (f.function.as_str(), f.call_site)).collect();
+    assert_eq!(
+        frames,
+        vec![
+            ("inner", Some(span("inner(0)"))),
+            ("outer", Some(span("outer()"))),
+            // called by the host
+            ("main", None),
+        ]
+    );
+}
+
+#[test]
+fn backtraces_are_rendered_after_the_diagnostic() {
+    let error = run_source(SOURCE).unwrap_err();
+    let mut sources = SourceMap::new();
+    sources.add("test.bs", SOURCE.to_string());
+    let rendered = error.format_with(&sources, "execution error", false);
+    let backtrace = rendered.split_once("\nbacktrace:").unwrap().1;
+    assert_eq!(
+        backtrace,
+        "
+    in 'inner' at test.bs:8:14
+    called from 'outer' at test.bs:5:12
+    called from 'main' at test.bs:2:12"
+    );
+}
+
+#[test]
+fn built_in_errors_get_the_frames_of_their_caller() {
+    // calling an undeclared function fails in the caller
+    let error = run_main("return helper();").unwrap_err();
+    let functions: Vec<_> = error.backtrace.iter().map(|f| f.function.as_str()).collect();
+    assert_eq!(functions, vec!["main"]);
+
+    let source = "fn main() { return helper(); } fn helper() { return int(\"x\"); }";
+    let error = run_source(source).unwrap_err();
+    let functions: Vec<_> = error.backtrace.iter().map(|f| f.function.as_str()).collect();
+    assert_eq!(functions, vec!["helper", "main"]);
+}
+
+#[test]
+fn endless_recursion_stops_at_the_call_depth_limit() {
+    let source = "fn f(n: Int) { if n == 0 { 0 } else { f(n - 1) } }\nfn main() { return f(-1); }";
+    // as deep as the main thread of the command line runs it
+    let error = std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(move || run_source(source).unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(error.code, Some(ErrorCode::RecursionLimit));
+    assert_eq!(error.backtrace.len(), MAX_CALL_DEPTH);
+    assert!(error.backtrace.iter().take(MAX_CALL_DEPTH - 1).all(|frame| frame.function == "f"));
+}
+
+#[test]
+fn repeated_calls_are_rendered_once() {
+    let source = "fn f(n: Int) { if n == 0 { 1 / 0 } else { f(n - 1) } }\nfn main() { return f(3); }";
+    let error = run_source(source).unwrap_err();
+    let mut sources = SourceMap::new();
+    sources.add("test.bs", source.to_string());
+    let rendered = error.format_with(&sources, "execution error", false);
+    let backtrace = rendered.split_once("\nbacktrace:").unwrap().1;
+    assert_eq!(
+        backtrace,
+        "
+    in 'f' at test.bs:1:30
+    called from 'f' at test.bs:1:43
+    ... repeated 2 more time(s)
+    called from 'main' at test.bs:2:20"
+    );
+}
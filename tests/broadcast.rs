@@ -0,0 +1,69 @@
+use bs::data::{ArithmeticError, DataType, Value};
+
+fn int(n: i64) -> Value {
+    Value::Data(DataType::Int(n))
+}
+
+fn array(values: Vec<Value>) -> Value {
+    Value::Array(values)
+}
+
+fn ints(values: &[i64]) -> Value {
+    array(values.iter().map(|n| int(*n)).collect())
+}
+
+fn same(lhs: Result<Value, ArithmeticError>, rhs: Value) {
+    assert_eq!(format!("{:?}", lhs), format!("{:?}", Ok::<_, ArithmeticError>(rhs)));
+}
+
+#[test]
+fn scalar_broadcasts_on_either_side() {
+    same(int(1).add(ints(&[1, 2])), ints(&[2, 3]));
+    same(ints(&[1, 2]).add(int(1)), ints(&[2, 3]));
+    same(int(10).sub(ints(&[1, 2])), ints(&[9, 8]));
+}
+
+#[test]
+fn lower_rank_repeats_along_outer_dimensions() {
+    let matrix = array(vec![ints(&[1, 2]), ints(&[3, 4])]);
+    same(
+        matrix.add(ints(&[10, 20])),
+        array(vec![ints(&[11, 22]), ints(&[13, 24])]),
+    );
+}
+
+#[test]
+fn dimensions_of_length_one_stretch() {
+    let column = array(vec![ints(&[1]), ints(&[2])]);
+    same(
+        column.mul(ints(&[1, 2, 3])),
+        array(vec![ints(&[1, 2, 3]), ints(&[2, 4, 6])]),
+    );
+    same(ints(&[1]).add(ints(&[])), ints(&[]));
+}
+
+#[test]
+fn mismatched_shapes_fail() {
+    assert_eq!(
+        ints(&[1, 2]).add(ints(&[1, 2, 3])).unwrap_err(),
+        ArithmeticError::ShapeMismatch {
+            lhs: vec![2],
+            rhs: vec![3]
+        }
+    );
+    let matrix = array(vec![ints(&[1, 2]), ints(&[3, 4])]);
+    assert_eq!(
+        matrix.add(ints(&[1, 2, 3])).unwrap_err(),
+        ArithmeticError::ShapeMismatch {
+            lhs: vec![2, 2],
+            rhs: vec![3]
+        }
+    );
+}
+
+#[test]
+fn ragged_arrays_fail() {
+    let ragged = array(vec![ints(&[1]), ints(&[2, 3])]);
+    assert_eq!(ragged.shape(), None);
+    assert_eq!(ragged.add(int(1)).unwrap_err(), ArithmeticError::Ragged);
+}
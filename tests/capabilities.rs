@@ -0,0 +1,138 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use bs::executor::Capabilities;
+use common::{run_main, run_with};
+
+/// an empty directory for one test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bs-capabilities-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn main_with(body: &str, capabilities: Capabilities) -> Result<Value, bs::error::ExecutionError> {
+    run_with(&format!("fn main() {{ {} }}", body), capabilities)
+}
+
+#[test]
+fn print_is_allowed_by_default_and_denied_by_none() {
+    assert_eq!(run_main("print(\"\"); return 1;").unwrap().repr(), "1");
+    let error = main_with("print(\"hi\");", Capabilities::none()).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::PermissionDenied));
+    assert!(error.cause.contains("`stdout`"), "{}", error.cause);
+}
+
+#[test]
+fn other_capabilities_are_denied_by_default() {
+    for call in ["env(\"PATH\")", "now()", "read_line()", "read_file(\"x\")", "write_file(\"x\", \"\")"] {
+        let error = run_main(&format!("return {};", call)).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::PermissionDenied), "{}", call);
+    }
+}
+
+#[test]
+fn permission_is_checked_before_arguments() {
+    let error = run_main("return env(1, 2);").unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::PermissionDenied));
+}
+
+#[test]
+fn granted_env_and_clock() {
+    let capabilities = Capabilities {
+        env: true,
+        clock: true,
+        ..Capabilities::default()
+    };
+    let unset = main_with("return env(\"BS_SURELY_UNSET_VARIABLE\");", capabilities.clone());
+    assert_eq!(unset.unwrap().repr(), "()");
+    match main_with("return now();", capabilities).unwrap() {
+        Value::Data(DataType::Float(seconds)) => assert!(seconds > 1.5e9),
+        other => panic!("expected a Float, found {:?}", other),
+    }
+}
+
+#[test]
+fn files_inside_granted_directories() {
+    let dir = temp_dir("files");
+    let file = dir.join("a.txt");
+    let capabilities = Capabilities {
+        fs_read: vec![dir.clone()],
+        fs_write: vec![dir.clone()],
+        ..Capabilities::default()
+    };
+    let body = format!(
+        "write_file({path:?}, \"contents\"); return read_file({path:?});",
+        path = file.to_str().unwrap()
+    );
+    assert_eq!(main_with(&body, capabilities.clone()).unwrap().repr(), "\"contents\"");
+
+    let missing = format!("return read_file({:?});", dir.join("missing").to_str().unwrap());
+    let error = main_with(&missing, capabilities).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::Io));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn files_outside_granted_directories_are_denied() {
+    let dir = temp_dir("outside");
+    let allowed = dir.join("allowed");
+    fs::create_dir(&allowed).unwrap();
+    fs::write(dir.join("secret.txt"), "secret").unwrap();
+    let capabilities = Capabilities {
+        fs_read: vec![allowed.clone()],
+        fs_write: vec![allowed.clone()],
+        ..Capabilities::default()
+    };
+    let outside = dir.join("secret.txt");
+    let escape = allowed.join("..").join("secret.txt");
+    for path in [&outside, &escape] {
+        let path = path.to_str().unwrap();
+        let read = main_with(&format!("return read_file({:?});", path), capabilities.clone());
+        let error = read.unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::PermissionDenied));
+        assert!(error.cause.contains(path), "{}", error.cause);
+
+        let write = main_with(&format!("write_file({:?}, \"x\");", path), capabilities.clone());
+        assert_eq!(write.unwrap_err().code, Some(ErrorCode::PermissionDenied));
+    }
+    assert_eq!(fs::read_to_string(&outside).unwrap(), "secret");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_links_out_of_granted_directories_are_denied() {
+    use std::os::unix::fs::symlink;
+
+    let dir = temp_dir("symlink");
+    let allowed = dir.join("allowed");
+    fs::create_dir(&allowed).unwrap();
+    fs::write(dir.join("secret.txt"), "secret").unwrap();
+    let capabilities = Capabilities {
+        fs_read: vec![allowed.clone()],
+        fs_write: vec![allowed.clone()],
+        ..Capabilities::default()
+    };
+    // a link to an existing file, and a dangling link that writing would create
+    let link = allowed.join("link");
+    symlink(dir.join("secret.txt"), &link).unwrap();
+    let dangling = allowed.join("dangling");
+    symlink(dir.join("created.txt"), &dangling).unwrap();
+
+    for path in [&link, &dangling] {
+        let path = path.to_str().unwrap();
+        let write = main_with(&format!("write_file({:?}, \"x\");", path), capabilities.clone());
+        assert_eq!(write.unwrap_err().code, Some(ErrorCode::PermissionDenied), "{}", path);
+        let read = main_with(&format!("return read_file({:?});", path), capabilities.clone());
+        assert_eq!(read.unwrap_err().code, Some(ErrorCode::PermissionDenied), "{}", path);
+    }
+    assert_eq!(fs::read_to_string(dir.join("secret.txt")).unwrap(), "secret");
+    assert!(!dir.join("created.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
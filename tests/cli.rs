@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// a script file for one test, removed when the test is done
+struct Script(PathBuf);
+impl Script {
+    fn new(name: &str, source: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("bs-cli-{}-{}.bs", name, std::process::id()));
+        fs::write(&path, source).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn bs(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bs")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn run_passes_arguments_to_the_entry_function() {
+    let script = Script::new("run", "fn main(a: String, b: String) { return [a, b]; } fn other() { print(\"other\"); }");
+    let path = script.path();
+    let output = bs(&["run", path, "--", "x", "--y"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "[\"x\", \"--y\"]\n");
+
+    let output = bs(&["run", path, "--entry", "other"]);
+    assert_eq!(stdout(&output), "other\n");
+}
+
+#[test]
+fn exit_codes() {
+    let failing = Script::new("failing", "fn main() { return 1 / 0; }");
+    let output = bs(&["run", failing.path(), "--no-color"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("E0009"), "{}", stderr(&output));
+    assert!(!stderr(&output).contains('\u{1b}'), "{}", stderr(&output));
+
+    let invalid = Script::new("invalid", "fn main( {");
+    assert_eq!(bs(&["run", invalid.path()]).status.code(), Some(3));
+    assert_eq!(bs(&["run"]).status.code(), Some(2));
+    assert_eq!(bs(&["frobnicate", "x.bs"]).status.code(), Some(2));
+}
+
+#[test]
+fn check_reports_undeclared_functions_without_running() {
+    let script = Script::new("check", "fn main() { print(\"ran\"); return prnt(1); }");
+    let output = bs(&["check", script.path(), "--no-color"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("'prnt' is not declared"), "{}", stderr(&output));
+
+    let script = Script::new("check-ok", "fn main() { return helper(); } fn helper() { return math::sin(1); }");
+    let output = bs(&["check", script.path()]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+}
+
+/// runs `bs repl` with `input` as stdin
+fn repl(input: &str) -> Output {
+    use std::io::Write;
+    use std::process::Stdio;
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bs"))
+        .args(["repl", "--no-color"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn repl_keeps_declarations_between_inputs() {
+    let input = "let x = 2;\nfn twice(n: Int) -> Int {\n    return n * 2;\n}\ntwice(x) + 1\n[1,\n 2]\n:funcs\n";
+    let output = repl(input);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "5\n[1, 2]\nfn twice(n: Int) -> Int\n");
+}
+
+#[test]
+fn repl_reset_forgets_variables() {
+    let output = repl("let x = 1;\n:reset\nx\n");
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("'x'"), "{}", stderr(&output));
+}
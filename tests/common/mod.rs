@@ -0,0 +1,59 @@
+// shared by the integration tests, not every test uses every helper
+#![allow(dead_code)]
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use bs::error::ExecutionError;
+use bs::executor::{Capabilities, Executor};
+use bs::lexer::tokenize;
+use bs::parser::{self, Ast};
+
+/// evaluates `expression` in the body of `main`
+pub fn run(expression: &str) -> Value {
+    run_main(&format!("return {};", expression)).unwrap()
+}
+
+pub fn run_main(body: &str) -> Result<Value, ExecutionError> {
+    run_source(&format!("fn main() {{ {} }}", body))
+}
+
+/// runs `main` of a whole script
+pub fn run_source(source: &str) -> Result<Value, ExecutionError> {
+    run_with(source, Capabilities::default())
+}
+
+/// runs `main` of a whole script, granting it `capabilities`
+pub fn run_with(source: &str, capabilities: Capabilities) -> Result<Value, ExecutionError> {
+    let (nodes, errors) = parser::parse(tokenize(source).unwrap());
+    assert!(errors.is_empty(), "{:?}", errors);
+    let executor = Executor::build_with(Ast::new(nodes), capabilities).unwrap();
+    executor.execute("main", vec![])
+}
+
+/// the result of `expression` as it would be written in a script
+pub fn repr(expression: &str) -> String {
+    run(expression).repr()
+}
+
+pub fn int(expression: &str) -> i64 {
+    match run(expression) {
+        Value::Data(DataType::Int(n)) => n,
+        other => panic!("expected an int from `{}`, found {:?}", expression, other),
+    }
+}
+
+pub fn float(expression: &str) -> f64 {
+    match run(expression) {
+        Value::Data(DataType::Float(n)) => n,
+        other => panic!("expected a float from `{}`, found {:?}", expression, other),
+    }
+}
+
+/// the error of evaluating `expression`, which must fail
+pub fn error(expression: &str) -> ExecutionError {
+    run_main(&format!("return {};", expression)).unwrap_err()
+}
+
+pub fn error_code(expression: &str) -> Option<ErrorCode> {
+    error(expression).code
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use common::{int, run, run_main, run_source};
+
+#[test]
+fn if_and_blocks_are_expressions() {
+    assert_eq!(int("if 1 < 2 { 1 } else { 2 }"), 1);
+    assert_eq!(int("if false { 1 } else if true { 2 } else { 3 }"), 2);
+    let value = run_main("let x = { let y = 2; y * 3 }; return x;");
+    assert!(matches!(value, Ok(Value::Data(DataType::Int(6)))), "{:?}", value);
+}
+
+#[test]
+fn statements_evaluate_to_unit() {
+    assert!(matches!(run_main("let x = 1;"), Ok(Value::Unit)));
+    assert!(matches!(run("if false { 1 }"), Value::Unit));
+    assert!(matches!(run_main("if true { return 1; } return 2;"), Ok(Value::Data(DataType::Int(1)))));
+    let err = run_main("if 1 { }").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::TypeMismatch));
+}
+
+#[test]
+fn return_inside_expressions_ends_the_function() {
+    let returned = |body: &str| run_main(body).unwrap().repr();
+    assert_eq!(returned("let x = { return 5; }; return 1;"), "5");
+    assert_eq!(returned("let x = 0; x = { return 5; }; return 1;"), "5");
+    assert_eq!(returned("return 1 + { return 5; };"), "5");
+    assert_eq!(returned("print({ return 5; }); return 1;"), "5");
+    assert_eq!(returned("let a = [1, { return 5; }]; return 1;"), "5");
+    assert_eq!(returned("if { return 5; } { 1 } return 1;"), "5");
+}
+
+#[test]
+fn blocks_assign_outer_variables() {
+    let returned = |body: &str| run_main(body).unwrap().repr();
+    // block and `if` expressions
+    assert_eq!(returned("let x = 1; let y = { x = 2; 3 }; return [x, y];"), "[2, 3]");
+    assert_eq!(returned("let x = 1; let z = if true { x = 5; 1 } else { 0 }; return [x, z];"), "[5, 1]");
+    // block and `if` statements
+    assert_eq!(returned("let x = 1; { x = 6; } return x;"), "6");
+    assert_eq!(returned("let x = 1; if true { x = 7; } return x;"), "7");
+    // an inner declaration hides the outer variable until its block ends
+    assert_eq!(returned("let x = 1; { let x = 2; x = 3; } return x;"), "1");
+}
+
+#[test]
+fn declarations_end_with_their_block() {
+    for body in ["if true { let leaked = 9; } return leaked;", "let y = { let leaked = 9; 1 }; return leaked;"] {
+        let err = run_main(body).unwrap_err();
+        assert_eq!(err.code, Some(ErrorCode::UndeclaredVariable), "{}", body);
+    }
+    // the top level scopes the same way, only its own declarations are globals
+    let source = "let x = 1; if true { x = 2; let leaked = 9; } fn main() { return leaked; }";
+    let err = run_source(source).unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::UndeclaredVariable));
+    let source = "let x = 1; let y = { x = 2; 3 }; fn main() { return [x, y]; }";
+    assert_eq!(run_source(source).unwrap().repr(), "[2, 3]");
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use common::{float, int, run, run_main};
+
+#[test]
+fn conversion_builtins() {
+    assert_eq!(int("int(2.7)"), 2);
+    assert_eq!(int("int(-2.7)"), -2);
+    assert_eq!(int("int(\"42\")"), 42);
+    assert_eq!(int("int(true)"), 1);
+    assert_eq!(float("float(3)"), 3.0);
+    assert_eq!(float("float(\"1.5\")"), 1.5);
+    assert!(matches!(run("str(2.0)"), Value::Data(DataType::String(s)) if s == "2.0"));
+    let err = run_main("return int(\"nope\");").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::InvalidValue));
+}
+
+#[test]
+fn rounding_builtins() {
+    assert_eq!(float("round(2.5)"), 3.0);
+    assert_eq!(float("round(-2.5)"), -3.0);
+    assert_eq!(float("floor(-1.5)"), -2.0);
+    assert_eq!(float("ceil(1.2)"), 2.0);
+    assert_eq!(int("floor(7)"), 7);
+}
@@ -0,0 +1,72 @@
+use bs::diagnostic::{Diagnostic, ErrorCode};
+use bs::source::SourceMap;
+use bs::span::Span;
+
+fn render(source: &str, diagnostic: Diagnostic) -> String {
+    let mut sources = SourceMap::new();
+    sources.add("test.bs", source.to_string());
+    diagnostic.format_with(&sources, "error", false)
+}
+
+/// span of the first occurrence of `text` in `source`
+fn find(source: &str, text: &str) -> Span {
+    let start = source.find(text).unwrap();
+    Span::new(start, start + text.len())
+}
+
+#[test]
+fn renders_location_snippet_and_notes() {
+    let source = "let x = 1;\nlet y = x + é;\n";
+    let diagnostic = Diagnostic::error("variable: 'é' is not declared".to_string(), find(source, "é"))
+        .with_code(ErrorCode::UndeclaredVariable)
+        .with_note("variables are declared with `let`".to_string())
+        .with_help("a variable with a similar name exists: 'x'".to_string());
+    let expected = "\
+error[E0005]:
+ --> test.bs:2:13
+  ▍
+2 ▍ let y = x + é;
+  ▍             ^
+variable: 'é' is not declared
+  = note: variables are declared with `let`
+  = help: a variable with a similar name exists: 'x'";
+    assert_eq!(render(source, diagnostic), expected);
+}
+
+#[test]
+fn multi_line_spans_and_secondary_labels() {
+    let source = "fn main() {\n\tlet a = [1,\n  2];\n".to_string() + &"\n".repeat(7) + "a + b;";
+    let array = find(&source, "[1,\n  2]");
+    let diagnostic = Diagnostic::error("shapes differ".to_string(), array)
+        .with_label(find(&source, "b"), "used here".to_string());
+    // tabs before the span are kept, so the underline starts below the bracket,
+    // the gutter is as wide as the largest line number
+    let expected = "\
+error:
+  --> test.bs:2:10
+   ▍
+ 2 ▍ \tlet a = [1,
+   ▍ \t        ^^^
+ 3 ▍   2];
+   ▍ ^^^^
+  ::: test.bs:11:5
+   ▍
+11 ▍ a + b;
+   ▍     - used here
+shapes differ";
+    assert_eq!(render(&source, diagnostic), expected);
+}
+
+#[test]
+fn empty_spans_get_a_single_marker() {
+    let source = "let x = 1";
+    let diagnostic = Diagnostic::error("expected `;`".to_string(), Span::new(9, 9));
+    let expected = "\
+error:
+ --> test.bs:1:10
+  ▍
+1 ▍ let x = 1
+  ▍          ^
+expected `;`";
+    assert_eq!(render(source, diagnostic), expected);
+}
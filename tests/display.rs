@@ -0,0 +1,33 @@
+use bs::data::{DataType, Value};
+
+fn string(s: &str) -> Value {
+    Value::Data(DataType::String(s.to_string()))
+}
+
+#[test]
+fn scalars_display_as_literals() {
+    assert_eq!(Value::Data(DataType::Int(-3)).to_string(), "-3");
+    assert_eq!(Value::Data(DataType::Float(1.0)).to_string(), "1.0");
+    assert_eq!(Value::Data(DataType::Float(2.5)).to_string(), "2.5");
+    assert_eq!(Value::Data(DataType::Bool(true)).to_string(), "true");
+    assert_eq!(Value::Unit.to_string(), "()");
+}
+
+#[test]
+fn strings_are_only_quoted_by_repr() {
+    assert_eq!(string("a \"b\"").to_string(), "a \"b\"");
+    assert_eq!(string("a \"b\"").repr(), "\"a \\\"b\\\"\"");
+    assert_eq!(string("{x}\n").repr(), "\"{{x}}\\n\"");
+}
+
+#[test]
+fn arrays_display_their_elements_as_literals() {
+    let array = Value::Array(vec![
+        Value::Data(DataType::Int(1)),
+        Value::Data(DataType::Float(2.5)),
+        string("a"),
+        Value::Array(vec![]),
+    ]);
+    assert_eq!(array.to_string(), "[1, 2.5, \"a\", []]");
+    assert_eq!(array.repr(), array.to_string());
+}
@@ -0,0 +1,62 @@
+use bs::error::ParseError;
+use bs::lexer::{tokenize, TokenKind};
+use bs::parser::{self, BinaryOperator};
+use bs::span::Span;
+
+fn expected(start: usize, expected: Vec<TokenKind>, found: Option<TokenKind>) -> ParseError {
+    ParseError::expected(Span::new(start, start + 1), expected, found)
+}
+
+/// cause of the first error in `source`
+fn cause(source: &str) -> String {
+    parser::parse(tokenize(source).unwrap()).1[0].cause.clone()
+}
+
+#[test]
+fn messages_name_the_expected_tokens() {
+    let error = expected(0, vec![TokenKind::Semicolon], Some(TokenKind::Identifier));
+    assert_eq!(error.cause, "expected `;` but found identifier");
+    let error = expected(0, vec![TokenKind::Comma, TokenKind::RightParen], None);
+    assert_eq!(error.cause, "expected one of `,`, `)` but found end of input");
+    let error = expected(0, Vec::new(), Some(TokenKind::RightBrace));
+    assert_eq!(error.cause, "unexpected `}`");
+}
+
+#[test]
+fn merged_errors_keep_the_furthest() {
+    let comma = expected(4, vec![TokenKind::Comma], Some(TokenKind::Identifier));
+    let paren = expected(4, vec![TokenKind::RightParen], Some(TokenKind::Identifier));
+    let semicolon = expected(9, vec![TokenKind::Semicolon], None);
+
+    let merged = comma.clone().merge(paren.clone());
+    assert_eq!(merged.expected, vec![TokenKind::Comma, TokenKind::RightParen]);
+    assert_eq!(merged.cause, "expected one of `,`, `)` but found identifier");
+    assert_eq!(paren.clone().merge(semicolon.clone()).cause, semicolon.cause);
+    assert_eq!(semicolon.clone().merge(paren).cause, semicolon.cause);
+    // a custom cause at the same position is more specific than an expectation
+    let custom = ParseError::new(Span::new(4, 5), "invalid identifier".to_string());
+    assert_eq!(comma.clone().merge(custom.clone()).cause, "invalid identifier");
+    assert_eq!(custom.merge(comma).cause, "invalid identifier");
+}
+
+#[test]
+fn expressions_and_operators_are_summarized() {
+    let mut tokens = TokenKind::EXPRESSION_START.to_vec();
+    tokens.extend(BinaryOperator::tokens());
+    tokens.push(TokenKind::Semicolon);
+    let error = expected(0, tokens, Some(TokenKind::Let));
+    assert_eq!(error.cause, "expected one of expression, operator, `;` but found `let`");
+
+    // only complete sets are summarized
+    let error = expected(0, vec![TokenKind::Identifier, TokenKind::Plus], None);
+    assert_eq!(error.cause, "expected one of identifier, `+` but found end of input");
+}
+
+#[test]
+fn every_expression_start_is_expected_where_a_value_is_missing() {
+    assert_eq!(cause("let x = }"), "expected expression but found `}`");
+    assert_eq!(cause("let x = [1, , 2];"), "expected expression but found `,`");
+    assert_eq!(cause("let x = 1 +"), "expected expression but found end of input");
+    assert_eq!(cause("let x = (1 2);"), "expected one of operator, `)` but found number");
+    assert_eq!(cause("if ; { }"), "expected expression but found `;`");
+}
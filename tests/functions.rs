@@ -0,0 +1,76 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use bs::error::ExecutionError;
+use bs::lexer::tokenize;
+use bs::parser;
+use common::run_source;
+
+const GREET: &str = "
+fn greet(name: String, greeting: String = \"hello\", end: String = greeting + \"!\") {
+    return greeting + \" \" + name + end;
+}
+";
+
+fn greet(call: &str) -> Result<String, ExecutionError> {
+    let source = format!("{} fn main() {{ return {}; }}", GREET, call);
+    run_source(&source).map(|value| value.to_string())
+}
+
+#[test]
+fn defaults_fill_in_missing_arguments() {
+    assert_eq!(greet("greet(\"a\")").unwrap(), "hello ahello!");
+    assert_eq!(greet("greet(\"a\", \"hi\")").unwrap(), "hi ahi!");
+    assert_eq!(greet("greet(\"a\", \"hi\", \".\")").unwrap(), "hi a.");
+}
+
+#[test]
+fn named_arguments_bind_by_name() {
+    assert_eq!(greet("greet(name: \"a\", end: \"?\")").unwrap(), "hello a?");
+    assert_eq!(greet("greet(\"a\", end: \"\", greeting: \"hey\")").unwrap(), "hey a");
+}
+
+#[test]
+fn invalid_arguments_point_at_the_call() {
+    let source = format!("{} fn main() {{\n  return greet(nme: \"a\");\n}}", GREET);
+    let err = run_source(&source).unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::InvalidArgument));
+    assert_eq!(err.help, vec!["a parameter with a similar name exists: 'name'".to_string()]);
+    let call = source.find("greet(nme").unwrap();
+    assert_eq!(err.span.start, call);
+
+    let err = greet("greet(\"a\", name: \"b\")").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::InvalidArgument));
+    assert_eq!(err.cause, "argument 'name' is given more than once");
+
+    let err = greet("greet(greeting: \"hi\")").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::ArgumentCount));
+    assert_eq!(err.cause, "missing argument for parameter 'name'");
+
+    let err = greet("greet(\"a\", \"b\", \"c\", \"d\")").unwrap_err();
+    assert_eq!(err.cause, "invalid function arguments, expected 1 to 3 value(s), found: 4");
+
+    let err = greet("math::sqrt(x: 4)").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::InvalidArgument));
+}
+
+#[test]
+fn parameter_and_argument_order_is_checked_when_parsing() {
+    let errors = |source: &str| parser::parse(tokenize(source).unwrap()).1;
+    let errs = errors("fn f(a: Int = 1, b: Int) { }");
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].cause, "parameter 'b' needs a default value");
+    let errs = errors("fn main() { f(a: 1, 2); }");
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].cause, "positional argument after a named argument");
+}
+
+#[test]
+fn script_functions_hide_built_ins() {
+    let source = "
+fn max(a: Int, b: Int) { if a > b { return a; } return b; }
+fn len(s: String) { return 0; }
+fn main() { return [max(1, 2), max(b: 5, a: 3), len(\"abc\"), sum([1, 2])]; }
+";
+    assert_eq!(run_source(source).unwrap().repr(), "[2, 5, 0, 3]");
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use bs::executor::Executor;
+use bs::lexer::tokenize;
+use bs::parser::{self, Ast};
+use common::{run_main, run_source};
+
+fn build(source: &str) -> Executor {
+    let (nodes, errors) = parser::parse(tokenize(source).unwrap());
+    assert!(errors.is_empty(), "{:?}", errors);
+    Executor::build(Ast::new(nodes)).unwrap()
+}
+
+fn global(executor: &Executor, name: &str) -> String {
+    executor.get_global(name).unwrap().repr()
+}
+
+#[test]
+fn host_reads_and_sets_globals() {
+    let executor = build("let limit = 2 * 5; const NAME = \"bs\"; fn over(n: Int) { return n > limit; }");
+    assert_eq!(global(&executor, "limit"), "10");
+    assert_eq!(global(&executor, "NAME"), "\"bs\"");
+    assert!(executor.get_global("missing").is_none());
+
+    executor.execute("over", vec![&11.0]).unwrap();
+    executor.set_global("limit", &Value::Data(DataType::Int(20))).unwrap();
+    assert_eq!(executor.execute("over", vec![&11.0]).unwrap().repr(), "false");
+
+    executor.set_global("added", &"new").unwrap();
+    let names: Vec<String> = executor.globals().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["NAME", "added", "limit", "math::E", "math::PI"]);
+}
+
+#[test]
+fn functions_assign_globals_across_calls() {
+    let executor = build("let count = 0; fn tick() { count += 1; return count; }");
+    executor.execute("tick", vec![]).unwrap();
+    assert_eq!(executor.execute("tick", vec![]).unwrap().repr(), "2");
+    assert_eq!(global(&executor, "count"), "2");
+}
+
+#[test]
+fn function_variables_hide_globals() {
+    let source = "let x = 1; fn f() { let x = 5; x = x + 1; return x; } fn main() { return [f(), x]; }";
+    assert_eq!(run_source(source).unwrap().repr(), "[6, 1]");
+}
+
+#[test]
+fn functions_do_not_see_the_variables_of_their_caller() {
+    let source = "fn inner() { return secret; } fn main() { let secret = 1; return inner(); }";
+    let error = run_source(source).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::UndeclaredVariable));
+}
+
+#[test]
+fn constants_cannot_be_assigned() {
+    let source = "const LIMIT = 3; fn main() { LIMIT = 4; }";
+    assert_eq!(run_source(source).unwrap_err().code, Some(ErrorCode::ConstantAssignment));
+
+    let error = run_main("const a = 1; a += 1; return a;").unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::ConstantAssignment));
+    let error = run_main("math::PI = 3;").unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::ConstantAssignment));
+
+    let executor = build("const LIMIT = 3;");
+    let error = executor.set_global("LIMIT", &1.0).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::ConstantAssignment));
+    assert_eq!(global(&executor, "LIMIT"), "3");
+}
+
+#[test]
+fn keywords_only_match_whole_words() {
+    assert_eq!(run_main("let letter = 1; let constant = 2; let iffy = 3; return letter + constant + iffy;").unwrap().repr(), "6");
+}
+
+#[test]
+fn nested_function_declarations_are_errors() {
+    let source = "fn main() { fn helper() { return 1; } return 2; }";
+    assert_eq!(run_source(source).unwrap_err().code, Some(ErrorCode::NestedFunction));
+
+    let (nodes, _) = parser::parse(tokenize(source).unwrap());
+    let errors = bs::executor::check(&Ast::new(nodes));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::NestedFunction));
+}
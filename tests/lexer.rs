@@ -0,0 +1,78 @@
+use bs::data::DataType;
+use bs::diagnostic::ErrorCode;
+use bs::lexer::{tokenize, StringPart, Token, TokenKind};
+use bs::span::Span;
+
+fn tokens(source: &str) -> Vec<Token> {
+    tokenize(source).unwrap().collect()
+}
+
+/// the value of a source that is a single string literal
+fn string(source: &str) -> String {
+    match tokens(source).as_slice() {
+        [Token { data: Some(DataType::String(s)), .. }] => s.clone(),
+        other => panic!("expected a single string from {}, found {:?}", source, other),
+    }
+}
+
+#[test]
+fn unknown_characters_are_invalid_tokens() {
+    let error = tokenize("let x = 1 @ 2;").unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidToken);
+    assert_eq!(error.span, Span::new(10, 11));
+    assert_eq!(error.cause, "invalid token '@'");
+
+    // the span covers the whole character
+    let error = tokenize("let é = 1;").unwrap_err();
+    assert_eq!(error.span, Span::new(4, 6));
+}
+
+#[test]
+fn escape_sequences() {
+    assert_eq!(string(r#""a\n\t\r\0\\\"\'""#), "a\n\t\r\0\\\"'");
+    assert_eq!(string(r#""\u{e9}\u{1F600}""#), "é😀");
+    // a backslash before the newline continues the line without its indentation
+    assert_eq!(string("\"a \\\n    b\""), "a b");
+
+    let error = tokenize(r#"let s = "a\qb";"#).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidToken);
+    assert_eq!(error.span, Span::new(10, 12));
+    assert_eq!(error.cause, "invalid escape sequence: '\\q'");
+    let error = tokenize(r#""\u{110000}""#).unwrap_err();
+    assert_eq!(error.span, Span::new(1, 11));
+    assert_eq!(error.cause, "invalid unicode escape, not a valid unicode scalar value");
+}
+
+#[test]
+fn raw_strings_take_their_content_verbatim() {
+    assert_eq!(string(r#"r"a\n{b}""#), r"a\n{b}");
+    assert_eq!(string(r##"r#"say "hi""#"##), r#"say "hi""#);
+    let token = &tokens(r#"x = r"\";"#)[2];
+    assert_eq!(token.kind, TokenKind::StringLiteral);
+    assert_eq!(token.span, Span::new(4, 8));
+}
+
+#[test]
+fn interpolation_embeds_located_expressions() {
+    let tokens = tokens(r#"s = "x = {a + 1}!";"#);
+    let token = &tokens[2];
+    assert_eq!(token.kind, TokenKind::InterpolatedString);
+    assert_eq!(token.span, Span::new(4, 18));
+    let [StringPart::Literal(before), StringPart::Expression(expression), StringPart::Literal(after)] =
+        token.parts.as_slice()
+    else {
+        panic!("unexpected parts {:?}", token.parts);
+    };
+    assert_eq!((before.as_str(), after.as_str()), ("x = ", "!"));
+    // the embedded tokens point into the whole source
+    let spans: Vec<Span> = expression.iter().map(|t| t.span).collect();
+    assert_eq!(spans, vec![Span::new(10, 11), Span::new(12, 13), Span::new(14, 15)]);
+
+    // doubled braces are literal
+    assert_eq!(string(r#""{{a}}""#), "{a}");
+
+    let error = tokenize(r#""a {b""#).unwrap_err();
+    assert_eq!(error.cause, "unterminated interpolation, missing closing '}'");
+    let error = tokenize(r#""a { }""#).unwrap_err();
+    assert_eq!(error.cause, "empty expression in string interpolation");
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use common::{float, int, run, run_main};
+
+#[test]
+fn math_accepts_ints_and_floats() {
+    assert_eq!(float("math::sin(0)"), 0.0);
+    assert_eq!(float("math::sqrt(16)"), 4.0);
+    assert_eq!(float("math::log(1000)"), 3.0);
+    assert_eq!(float("math::log(8, 2)"), 3.0);
+    assert_eq!(float("math::ln(math::E)"), 1.0);
+    assert_eq!(float("math::cos(math::PI)"), -1.0);
+    assert_eq!(int("math::pow(2, 10)"), 1024);
+    assert_eq!(int("math::abs(-3)"), 3);
+    assert_eq!(float("math::abs(-3.5)"), 3.5);
+    assert_eq!(int("math::min(3, 1, 2)"), 1);
+    assert_eq!(float("math::max(1, 2.5)"), 2.5);
+    assert_eq!(int("math::clamp(15, 0, 10)"), 10);
+}
+
+#[test]
+fn math_applies_element_wise() {
+    let value = run("math::abs([-1, [2, -3]])");
+    assert_eq!(value.repr(), "[1, [2, 3]]");
+    let value = run("math::max([1, 5, 3], 2)");
+    assert_eq!(value.repr(), "[2, 5, 3]");
+    let value = run("math::clamp([-1.0, 0.5, 2.0], 0, 1)");
+    assert_eq!(value.repr(), "[0.0, 0.5, 1.0]");
+}
+
+#[test]
+fn math_reports_invalid_arguments() {
+    let err = run_main("return math::sqrt(1, 2);").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::ArgumentCount));
+    let err = run_main("return math::sqrt([1, \"a\"]);").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::TypeMismatch));
+    assert!(err.cause.contains("argument 1"), "{}", err.cause);
+    let err = run_main("return math::min([1, 2], [1, 2, 3]);").unwrap_err();
+    assert_eq!(err.code, Some(ErrorCode::ShapeMismatch));
+}
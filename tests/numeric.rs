@@ -0,0 +1,95 @@
+use bs::data::{ArithmeticError, DataType};
+use quickcheck::quickcheck;
+
+type Op = fn(&DataType, DataType) -> Result<DataType, ArithmeticError>;
+
+/// equality that treats every `NaN` as equal, as `NaN != NaN` would break every property
+fn same(lhs: &Result<DataType, ArithmeticError>, rhs: &Result<DataType, ArithmeticError>) -> bool {
+    match (lhs, rhs) {
+        (Ok(DataType::Float(l)), Ok(DataType::Float(r))) => l == r || (l.is_nan() && r.is_nan()),
+        _ => lhs == rhs,
+    }
+}
+
+fn commutes(op: Op, lhs: DataType, rhs: DataType) -> bool {
+    same(&op(&lhs, rhs.clone()), &op(&rhs, lhs))
+}
+
+quickcheck! {
+    fn add_commutes_for_ints(l: i64, r: i64) -> bool {
+        commutes(DataType::add, DataType::Int(l), DataType::Int(r))
+    }
+
+    fn add_commutes_for_floats(l: f64, r: f64) -> bool {
+        commutes(DataType::add, DataType::Float(l), DataType::Float(r))
+    }
+
+    fn add_commutes_for_mixed(l: i64, r: f64) -> bool {
+        commutes(DataType::add, DataType::Int(l), DataType::Float(r))
+    }
+
+    fn mul_commutes_for_ints(l: i64, r: i64) -> bool {
+        commutes(DataType::mul, DataType::Int(l), DataType::Int(r))
+    }
+
+    fn mul_commutes_for_floats(l: f64, r: f64) -> bool {
+        commutes(DataType::mul, DataType::Float(l), DataType::Float(r))
+    }
+
+    fn mul_commutes_for_mixed(l: i64, r: f64) -> bool {
+        commutes(DataType::mul, DataType::Int(l), DataType::Float(r))
+    }
+
+    fn mixed_arithmetic_promotes_to_float(l: i64, r: f64) -> bool {
+        let ops: [Op; 4] = [DataType::add, DataType::sub, DataType::mul, DataType::div];
+        ops.iter().all(|op| {
+            matches!(op(&DataType::Int(l), DataType::Float(r)), Ok(DataType::Float(_)))
+                && matches!(op(&DataType::Float(r), DataType::Int(l)), Ok(DataType::Float(_)))
+        })
+    }
+
+    fn mixed_arithmetic_equals_float_arithmetic(l: i64, r: f64) -> bool {
+        let ops: [Op; 4] = [DataType::add, DataType::sub, DataType::mul, DataType::div];
+        ops.iter().all(|op| {
+            same(
+                &op(&DataType::Int(l), DataType::Float(r)),
+                &op(&DataType::Float(l as f64), DataType::Float(r)),
+            )
+        })
+    }
+
+    fn int_arithmetic_is_checked(l: i64, r: i64) -> bool {
+        let expect = |result: Option<i64>| result.map(DataType::Int).ok_or(ArithmeticError::Overflow);
+        DataType::Int(l).add(DataType::Int(r)) == expect(l.checked_add(r))
+            && DataType::Int(l).sub(DataType::Int(r)) == expect(l.checked_sub(r))
+            && DataType::Int(l).mul(DataType::Int(r)) == expect(l.checked_mul(r))
+    }
+
+    fn int_division_by_zero_fails(l: i64) -> bool {
+        DataType::Int(l).div(DataType::Int(0)) == Err(ArithmeticError::DivisionByZero)
+    }
+}
+
+#[test]
+fn mixed_addition_does_not_truncate() {
+    assert_eq!(
+        DataType::Int(1).add(DataType::Float(0.5)),
+        Ok(DataType::Float(1.5))
+    );
+    assert_eq!(
+        DataType::Float(0.5).add(DataType::Int(1)),
+        Ok(DataType::Float(1.5))
+    );
+}
+
+#[test]
+fn float_division_by_zero_follows_ieee() {
+    assert_eq!(
+        DataType::Float(1.0).div(DataType::Int(0)),
+        Ok(DataType::Float(f64::INFINITY))
+    );
+    assert_eq!(
+        DataType::Int(-1).div(DataType::Float(0.0)),
+        Ok(DataType::Float(f64::NEG_INFINITY))
+    );
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use bs::data::{ArithmeticError, DataType, Value, MAX_LENGTH};
+use bs::diagnostic::ErrorCode;
+use common::{float, int, repr, run, run_main};
+
+fn string(s: &str) -> DataType {
+    DataType::String(s.to_string())
+}
+
+#[test]
+fn strings_concatenate_with_numbers_and_bools() {
+    assert_eq!(string("a").add(string("b")), Ok(string("ab")));
+    assert_eq!(string("n: ").add(DataType::Int(1)), Ok(string("n: 1")));
+    assert_eq!(DataType::Bool(true).add(string("!")), Ok(string("true!")));
+}
+
+#[test]
+fn strings_repeat() {
+    assert_eq!(string("ab").mul(DataType::Int(3)), Ok(string("ababab")));
+    assert_eq!(DataType::Int(0).mul(string("ab")), Ok(string("")));
+    assert_eq!(
+        string("ab").mul(DataType::Int(-1)),
+        Err(ArithmeticError::NegativeCount)
+    );
+    // the host would abort if the allocation failed
+    assert_eq!(
+        string("a").mul(DataType::Int(100_000_000_000)),
+        Err(ArithmeticError::TooLarge)
+    );
+    assert_eq!(string("ab").mul(DataType::Int(i64::MAX)), Err(ArithmeticError::TooLarge));
+    let longest = string("a").mul(DataType::Int(MAX_LENGTH as i64));
+    assert!(matches!(longest, Ok(DataType::String(s)) if s.len() == MAX_LENGTH));
+}
+
+#[test]
+fn comparisons() {
+    assert_eq!(string("a").lt(string("b")), Ok(DataType::Bool(true)));
+    assert_eq!(DataType::Int(1).eq(DataType::Float(1.0)), Ok(DataType::Bool(true)));
+    assert_eq!(DataType::Int(2).ge(DataType::Float(2.5)), Ok(DataType::Bool(false)));
+    assert_eq!(
+        DataType::Float(f64::NAN).le(DataType::Float(f64::NAN)),
+        Ok(DataType::Bool(false))
+    );
+    assert_eq!(DataType::Bool(true).ne(DataType::Bool(false)), Ok(DataType::Bool(true)));
+    // values of different types are not equal
+    assert_eq!(string("a").eq(DataType::Int(1)), Ok(DataType::Bool(false)));
+    assert_eq!(DataType::Bool(true).ne(DataType::Int(1)), Ok(DataType::Bool(true)));
+}
+
+#[test]
+fn logical_operators() {
+    let t = DataType::Bool(true);
+    let f = DataType::Bool(false);
+    assert_eq!(t.and(f.clone()), Ok(f.clone()));
+    assert_eq!(t.or(f.clone()), Ok(t.clone()));
+    assert_eq!(t.bit_xor(t.clone()), Ok(f.clone()));
+    assert_eq!(t.not(), Ok(f));
+}
+
+#[test]
+fn not_in_scripts() {
+    assert_eq!(run("!(1 > 2) && !false").repr(), "true");
+    // binds tighter than binary operators
+    assert_eq!(run("!true || true").repr(), "true");
+    assert_eq!(run("![true, false]").repr(), "[false, true]");
+    assert_eq!(run("!(\"a\" == 1)").repr(), "true");
+
+    let error = run_main("return !1;").unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::InvalidOperands));
+    assert_eq!(error.cause, "cannot apply `!` to `Int`");
+}
+
+#[test]
+fn unsupported_operands_name_both_types() {
+    assert_eq!(
+        DataType::Bool(true).add(DataType::Int(1)),
+        Err(ArithmeticError::InvalidOperands {
+            lhs: "Bool",
+            rhs: "Int"
+        })
+    );
+    assert_eq!(
+        string("a").lt(DataType::Int(1)),
+        Err(ArithmeticError::InvalidOperands {
+            lhs: "String",
+            rhs: "Int"
+        })
+    );
+}
+
+#[test]
+fn operator_precedence() {
+    assert_eq!(int("1 + 2 * 3"), 7);
+    assert_eq!(int("10 - 4 - 3"), 3);
+    assert_eq!(int("2 ** 3 ** 2"), 512);
+    assert_eq!(int("1 | 2 ^ 3 & 4 << 1"), 3);
+    assert_eq!(int("-7 % 3"), -1);
+    assert_eq!(float("2 ** -1"), 0.5);
+}
+
+#[test]
+fn compound_assignment() {
+    let value = run_main("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x %= 4; return x;");
+    assert!(matches!(value, Ok(Value::Data(DataType::Int(2)))), "{:?}", value);
+}
+
+#[test]
+fn equality_compares_whole_values() {
+    assert_eq!(repr("[1, 2] == [1, 2]"), "true");
+    assert_eq!(int("if [1, 2] == [1, 2] { 1 } else { 0 }"), 1);
+    // different lengths and types are not equal, instead of failing to broadcast
+    assert_eq!(repr("[1, 2] == [1, 2, 3]"), "false");
+    assert_eq!(repr("[1] == 1"), "false");
+    assert_eq!(repr("[[1], \"a\"] != [[1.0], \"a\"]"), "false");
+    // ordering still broadcasts
+    assert_eq!(repr("[1, 2] < [2, 2]"), "[true, false]");
+}
+
+#[test]
+fn concatenation_is_limited_too() {
+    let half = string(&"a".repeat(MAX_LENGTH / 2));
+    let longest = half.add(half.clone());
+    assert!(matches!(longest, Ok(DataType::String(s)) if s.len() == MAX_LENGTH));
+    assert_eq!(half.add(string(&"a".repeat(MAX_LENGTH / 2 + 1))), Err(ArithmeticError::TooLarge));
+    assert_eq!(string(&"a".repeat(MAX_LENGTH)).add(DataType::Int(1)), Err(ArithmeticError::TooLarge));
+
+    let error = run_main("let s = \"a\" * 10000000; return \"{s}{s}\";").unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::TooLarge));
+}
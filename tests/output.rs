@@ -0,0 +1,45 @@
+use bs::executor::{Capabilities, Executor};
+use bs::lexer::tokenize;
+use bs::parser::{self, Ast};
+
+fn build(source: &str) -> Executor {
+    let (nodes, errors) = parser::parse(tokenize(source).unwrap());
+    assert!(errors.is_empty(), "{:?}", errors);
+    Executor::build(Ast::new(nodes)).unwrap()
+}
+
+#[test]
+fn print_writes_to_the_captured_output() {
+    let executor = build("fn main() { print(\"a\", 1); print([1, \"b\"]); }");
+    let output = executor.capture_output();
+    executor.execute("main", vec![]).unwrap();
+    assert_eq!(output.contents(), "a1\n[1, \"b\"]\n");
+}
+
+#[test]
+fn output_accumulates_until_taken() {
+    let executor = build("fn greet(name: String) { print(\"hi \" + name); }");
+    let output = executor.capture_output();
+    executor.execute("greet", vec![&"a"]).unwrap();
+    executor.execute("greet", vec![&"b"]).unwrap();
+    assert_eq!(output.take(), "hi a\nhi b\n");
+    executor.execute("greet", vec![&"c"]).unwrap();
+    assert_eq!(output.contents(), "hi c\n");
+}
+
+#[test]
+fn output_is_kept_when_the_script_fails() {
+    let executor = build("fn main() { print(\"before\"); return 1 / 0; }");
+    let output = executor.capture_output();
+    assert!(executor.execute("main", vec![]).is_err());
+    assert_eq!(output.contents(), "before\n");
+}
+
+#[test]
+fn denied_print_writes_nothing() {
+    let (nodes, _) = parser::parse(tokenize("fn main() { print(\"x\"); }").unwrap());
+    let executor = Executor::build_with(Ast::new(nodes), Capabilities::none()).unwrap();
+    let output = executor.capture_output();
+    assert!(executor.execute("main", vec![]).is_err());
+    assert_eq!(output.contents(), "");
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use bs::span::Span;
+use common::{error, float, int};
+
+#[test]
+fn integer_overflow_fails_at_the_operator() {
+    for (expression, operator) in [
+        ("9223372036854775807 + 1", "+"),
+        ("-9223372036854775807 - 2", "-"),
+        ("4611686018427387904 * 2", "*"),
+        ("2 ** 64", "**"),
+        ("1 << 64", "<<"),
+        ("(-9223372036854775807 - 1) / -1", "/"),
+    ] {
+        let err = error(expression);
+        assert_eq!(err.code, Some(ErrorCode::Overflow), "{}", expression);
+        assert_eq!(err.cause, format!("integer overflow in `{}`", operator));
+        // `error` wraps the expression into `fn main() { return ...; }`
+        let start = "fn main() { return ".len() + expression.rfind(operator).unwrap();
+        assert_eq!(err.span, Span::new(start, start + operator.len()), "{}", expression);
+    }
+    // the largest values still work
+    assert_eq!(int("9223372036854775806 + 1"), i64::MAX);
+    assert_eq!(int("-9223372036854775807 - 1"), i64::MIN);
+}
+
+#[test]
+fn integer_division_by_zero_points_at_the_divisor() {
+    for expression in ["1 / 0", "5 % 0", "[1, 2] / [1, 0]"] {
+        let err = error(expression);
+        assert_eq!(err.code, Some(ErrorCode::DivisionByZero), "{}", expression);
+        assert_eq!(err.cause, "attempt to divide by zero");
+        assert_eq!(err.labels.len(), 1);
+        assert_eq!(err.labels[0].message, "divided by this");
+    }
+    // floats follow IEEE 754
+    assert_eq!(float("1.0 / 0"), f64::INFINITY);
+    assert!(float("0.0 % 0").is_nan());
+}
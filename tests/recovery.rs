@@ -0,0 +1,116 @@
+use bs::diagnostic::ErrorCode;
+use bs::error::ParseError;
+use bs::lexer::tokenize;
+use bs::parser::{self, AstNode, AstNodeData, Data};
+use bs::span::Span;
+
+fn errors(source: &str) -> Vec<ParseError> {
+    parser::parse(tokenize(source).unwrap()).1
+}
+
+#[test]
+fn top_level_statements_need_semicolons() {
+    let errs = errors("let a = 1\nlet b = 2;\nfn main() { return a; }");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].span, Span::new(10, 13));
+    assert!(errs[0].cause.ends_with("`;` but found `let`"), "{}", errs[0].cause);
+
+    // block-like statements go without
+    assert!(errors("fn main() { } if true { } { }").is_empty());
+}
+
+#[test]
+fn missing_operands_are_reported_where_they_are_missing() {
+    let errs = errors("let g = 3 +;\nlet h = 4;");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].span, Span::new(11, 12));
+    assert!(errs[0].cause.ends_with("but found `;`"), "{}", errs[0].cause);
+
+    let errs = errors("fn main() { let g = 3 +; return g; }");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].span, Span::new(23, 24));
+}
+
+#[test]
+fn expected_tokens_fit_the_position() {
+    let cause = |source: &str| errors(source)[0].cause.clone();
+    // statements cannot be values
+    assert_eq!(cause("let x = ;"), "expected expression but found `;`");
+    assert_eq!(cause("let x = 1 + let;"), "expected expression but found `let`");
+    assert_eq!(cause("fn main() { f(1 2); }"), "expected one of operator, `,`, `)` but found number");
+    assert_eq!(
+        cause("let a = 1; )"),
+        "expected one of expression, `fn`, `let`, `const`, `return` but found `)`"
+    );
+}
+
+#[test]
+fn integer_literals_that_do_not_fit_overflow() {
+    let errs = errors("let big = 99999999999999999999;");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].code, ErrorCode::Overflow);
+    assert_eq!(errs[0].span, Span::new(10, 30));
+    assert_eq!(errs[0].cause, "integer literal is too large");
+
+    assert!(errors("let small = -9223372036854775808;").is_empty());
+}
+
+#[test]
+fn parsing_goes_on_after_errors() {
+    let source = "\
+fn a() { let = 1; let b = 2; b + ; }
+let c = (1 + ;
+}
+fn d() { return 1 }
+let e = 1;";
+    let (nodes, errs) = parser::parse(tokenize(source).unwrap());
+    let causes: Vec<(usize, &str)> = errs.iter().map(|e| (e.span.start, e.cause.as_str())).collect();
+    assert_eq!(
+        causes,
+        vec![
+            (13, "expected identifier but found `=`"),
+            (33, "expected expression but found `;`"),
+            (50, "expected expression but found `;`"),
+            (52, "expected one of expression, `fn`, `let`, `const`, `return` but found `}`"),
+            (72, "expected one of operator, `;` but found `}`"),
+        ]
+    );
+    // the statements after every error are still parsed
+    let last = nodes.last().unwrap();
+    assert!(matches!(&last.data, AstNodeData::VarDeclaration { name, .. } if name == "e"), "{:?}", last);
+    // errors inside blocks stay in the tree, `let b = 2;` in between is kept,
+    // as is the `b` before the missing operand
+    let AstNodeData::FnDeclaration { body, .. } = &nodes[0].data else {
+        panic!("expected a function, found {:?}", nodes[0]);
+    };
+    let AstNodeData::Block { block, .. } = &body.data else {
+        panic!("expected a block, found {:?}", body);
+    };
+    let invalid = |node: &AstNode| matches!(node.data, AstNodeData::Invalid { .. });
+    let kinds: Vec<bool> = block.iter().map(invalid).collect();
+    assert_eq!(kinds, vec![true, false, false, true]);
+}
+
+#[test]
+fn repeated_array_lengths() {
+    let length = |source: &str| match &parser::parse(tokenize(source).unwrap()).0[0].data {
+        AstNodeData::VarDeclaration { value, .. } => match &value.data {
+            AstNodeData::Data { data: Data::Repeat { count, .. } } => *count,
+            other => panic!("expected a repeated array, found {:?}", other),
+        },
+        other => panic!("expected a declaration, found {:?}", other),
+    };
+    assert_eq!(length("let a = [0; 3];"), 3);
+    assert_eq!(length("let a = [0; 0];"), 0);
+
+    let errs = errors("let a = [0; -9223372036854775808];");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].span, Span::new(12, 32));
+    assert_eq!(errs[0].cause, "array length cannot be negative");
+    let errs = errors("let a = [0; 300000000];");
+    assert_eq!(errs.len(), 1, "{:?}", errs);
+    assert_eq!(errs[0].code, ErrorCode::TooLarge);
+    assert_eq!(errs[0].span, Span::new(12, 21));
+    // the rest of the array is not parsed as statements
+    assert_eq!(errors("fn main() { let a = [0; -1]; return a; }").len(), 1);
+}
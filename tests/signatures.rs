@@ -0,0 +1,47 @@
+mod common;
+
+use bs::diagnostic::ErrorCode;
+use common::{error, run};
+
+#[test]
+fn arity_is_checked_before_the_call() {
+    let err = error("math::sin()");
+    assert_eq!(err.code, Some(ErrorCode::ArgumentCount));
+    assert_eq!(err.notes, vec!["'math::sin' takes (x: Number)".to_string()]);
+    assert_eq!(error("sin(1, 2)").code, Some(ErrorCode::ArgumentCount));
+    assert_eq!(error("substring(\"a\")").code, Some(ErrorCode::ArgumentCount));
+    assert_eq!(error("concat([1])").code, Some(ErrorCode::ArgumentCount));
+}
+
+#[test]
+fn signatures_show_optional_and_variadic_parameters() {
+    let err = error("parse_int()");
+    assert_eq!(err.notes, vec!["'parse_int' takes (s: String, radix: Int = 10)".to_string()]);
+    let err = error("range()");
+    assert_eq!(
+        err.notes,
+        vec!["'range' takes (start: Int, end?: Int, step: Int = 1)".to_string()]
+    );
+    let err = error("math::min(1)");
+    assert_eq!(
+        err.notes,
+        vec!["'math::min' takes (a: Number, b: Number, ...rest: Number)".to_string()]
+    );
+}
+
+#[test]
+fn types_are_checked_per_argument() {
+    let err = error("replace(\"a\", 1, \"b\")");
+    assert_eq!(err.code, Some(ErrorCode::TypeMismatch));
+    assert_eq!(err.cause, "argument 2 of 'replace' must be a string, found `Int`");
+    let err = error("math::max(1, 2, [3, true])");
+    assert_eq!(err.cause, "argument 3 of 'math::max' must be a number, found `Bool`");
+}
+
+#[test]
+fn defaults_are_filled_in() {
+    assert_eq!(run("parse_int(\"10\")").repr(), "10");
+    assert_eq!(run("join([\"a\", \"b\"])").repr(), "\"ab\"");
+    assert_eq!(run("math::log(100)").repr(), "2.0");
+    assert_eq!(run("range(2, 8, 3)").repr(), "[2, 5]");
+}
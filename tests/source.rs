@@ -0,0 +1,58 @@
+use bs::lexer::tokenize_file;
+use bs::source::{Location, SourceFile, SourceMap};
+use bs::span::Span;
+use std::path::PathBuf;
+
+fn file(source: &str) -> SourceFile {
+    SourceFile::new(PathBuf::from("test.bs"), source.to_string())
+}
+
+#[test]
+fn columns_count_chars() {
+    let file = file("let a = 1;\nlet é = \"ü\"; x\n");
+    assert_eq!(file.line_col(0), (1, 1));
+    assert_eq!(file.line_col(4), (1, 5));
+    // the line break belongs to the line it ends
+    assert_eq!(file.line_col(10), (1, 11));
+    assert_eq!(file.line_col(11), (2, 1));
+    // `é` and `ü` take two bytes but one column
+    assert_eq!(file.line_col(18), (2, 7));
+    assert_eq!(file.line_col(23), (2, 11));
+    // offsets inside a char or past the end are clamped
+    assert_eq!(file.line_col(16), (2, 5));
+    assert_eq!(file.line_col(100), (3, 1));
+}
+
+#[test]
+fn lines_touched_by_a_span() {
+    let file = file("a\r\nbc\nd");
+    assert_eq!(file.lines(Span::new(0, 1)), vec![(1, 0, "a")]);
+    // a span ending right after a line break stays on its line, `\r` is not shown
+    assert_eq!(file.lines(Span::new(0, 3)), vec![(1, 0, "a")]);
+    assert_eq!(file.lines(Span::new(1, 7)), vec![(1, 0, "a"), (2, 3, "bc"), (3, 6, "d")]);
+    // empty spans still show their line
+    assert_eq!(file.lines(Span::new(8, 8)), vec![(3, 6, "d")]);
+}
+
+#[test]
+fn token_spans_locate_in_their_file() {
+    let mut sources = SourceMap::new();
+    sources.add("first.bs", "let a = 1;".to_string());
+    let second = sources.add("second.bs", "\n  \"ü\" + b".to_string());
+    let tokens: Vec<_> = tokenize_file(sources.source(second).unwrap(), second)
+        .unwrap()
+        .collect();
+    let b = tokens.last().unwrap();
+    assert_eq!(b.span, Span::new(10, 11).with_file(second));
+    assert_eq!(
+        sources.location(b.span),
+        Some(Location {
+            path: PathBuf::from("second.bs"),
+            line: 2,
+            column: 9,
+        })
+    );
+    assert_eq!(sources.location(b.span).unwrap().to_string(), "second.bs:2:9");
+    // spans without a file refer to the first one
+    assert_eq!(sources.location(Span::new(4, 5)).unwrap().to_string(), "first.bs:1:5");
+}
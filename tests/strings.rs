@@ -0,0 +1,76 @@
+mod common;
+
+use bs::data::{DataType, Value};
+use bs::diagnostic::ErrorCode;
+use common::{error_code, repr, run, run_source};
+
+#[test]
+fn length_counts_characters() {
+    assert!(matches!(run("len(\"héllo\")"), Value::Data(DataType::Int(5))));
+    assert!(matches!(run("len(\"\")"), Value::Data(DataType::Int(0))));
+}
+
+#[test]
+fn case_and_whitespace() {
+    assert_eq!(repr("upper(\"straße\")"), "\"STRASSE\"");
+    assert_eq!(repr("lower(\"ABC\")"), "\"abc\"");
+    assert_eq!(repr("trim(\"  a b \\n\")"), "\"a b\"");
+}
+
+#[test]
+fn split_and_join() {
+    assert_eq!(repr("split(\"a,b,,c\", \",\")"), "[\"a\", \"b\", \"\", \"c\"]");
+    assert_eq!(repr("split(\" a  b \")"), "[\"a\", \"b\"]");
+    assert_eq!(repr("join([\"a\", \"b\"], \", \")"), "\"a, b\"");
+    assert_eq!(repr("join(split(\"a b\"))"), "\"ab\"");
+    assert_eq!(error_code("split(\"a\", \"\")"), Some(ErrorCode::InvalidValue));
+    assert_eq!(error_code("join([\"a\", 1])"), Some(ErrorCode::TypeMismatch));
+}
+
+#[test]
+fn searching() {
+    assert_eq!(repr("replace(\"a-b-c\", \"-\", \"+\")"), "\"a+b+c\"");
+    assert_eq!(repr("contains(\"hello\", \"ell\")"), "true");
+    assert_eq!(repr("starts_with(\"hello\", \"he\")"), "true");
+    assert_eq!(repr("ends_with(\"hello\", \"he\")"), "false");
+    assert_eq!(repr("find(\"日本語\", \"語\")"), "2");
+    assert_eq!(repr("find(\"abc\", \"d\")"), "-1");
+}
+
+#[test]
+fn substrings_use_character_indices() {
+    assert_eq!(repr("substring(\"日本語です\", 1, 3)"), "\"本語\"");
+    assert_eq!(repr("substring(\"hello\", 2)"), "\"llo\"");
+    assert_eq!(repr("chars(\"añb\")"), "[\"a\", \"ñ\", \"b\"]");
+    assert_eq!(error_code("substring(\"abc\", 2, 4)"), Some(ErrorCode::InvalidValue));
+    assert_eq!(error_code("substring(\"abc\", 2, 1)"), Some(ErrorCode::InvalidValue));
+}
+
+#[test]
+fn parsing() {
+    assert_eq!(repr("parse_int(\" 42 \")"), "42");
+    assert_eq!(repr("parse_int(\"ff\", 16)"), "255");
+    assert_eq!(repr("parse_float(\"2.5\")"), "2.5");
+    assert_eq!(error_code("parse_int(\"2.5\")"), Some(ErrorCode::InvalidValue));
+    assert_eq!(error_code("parse_float(1)"), Some(ErrorCode::TypeMismatch));
+}
+
+#[test]
+fn scripts_keep_their_own_string_functions() {
+    // scripts written before the string built-ins existed still call their own functions
+    let source = "
+fn split(s: String) { return \"own split\"; }
+fn trim(s: String, width: Int) { return width; }
+fn main() { return [split(\"a b\"), trim(\" a \", 3), upper(\"a\")]; }
+";
+    assert_eq!(run_source(source).unwrap().repr(), "[\"own split\", 3, \"A\"]");
+}
+
+#[test]
+fn results_longer_than_the_limit_are_errors() {
+    let code = error_code("replace(\"a\" * 100000, \"a\", \"a\" * 100000)");
+    assert_eq!(code, Some(ErrorCode::TooLarge));
+    let code = error_code("join([\"a\" * 10000000, \"a\" * 10000000], \"b\")");
+    assert_eq!(code, Some(ErrorCode::TooLarge));
+    assert_eq!(repr("len(join([\"a\" * 8000000, \"a\" * 8000000], \"b\" * 777216))"), "16777216");
+}